@@ -0,0 +1,76 @@
+use crate::datetime::{days_from_civil, parse_date};
+
+/// A named set of excluded days, from a `[calendar:<name>]` section.
+/// Jobs that `exclude` it never run on those days.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Calendar {
+    pub name: String,
+    /// Inclusive ranges of days since the epoch, sorted and non-overlapping.
+    ranges: Vec<(i64, i64)>,
+}
+
+impl Calendar {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            ranges: Vec::new(),
+        }
+    }
+
+    /// Adds a `YYYY-MM-DD` date or an inclusive `YYYY-MM-DD..YYYY-MM-DD` range.
+    pub fn add(&mut self, entry: &str) -> Result<(), String> {
+        let entry = entry.trim();
+        let (first, last) = match entry.split_once("..") {
+            Some((a, b)) => (a, b),
+            None => (entry, entry),
+        };
+
+        let (y, m, d) = parse_date(first)?;
+        let start = days_from_civil(y, m, d);
+        let (y, m, d) = parse_date(last)?;
+        let end = days_from_civil(y, m, d);
+        if start > end {
+            return Err(format!("Invalid date range: {}", entry));
+        }
+
+        self.ranges.push((start, end));
+        self.ranges.sort_unstable();
+
+        // Merge overlapping and adjacent ranges
+        let mut merged: Vec<(i64, i64)> = Vec::with_capacity(self.ranges.len());
+        for &(s, e) in &self.ranges {
+            match merged.last_mut() {
+                Some(last) if s <= last.1 + 1 => last.1 = last.1.max(e),
+                _ => merged.push((s, e)),
+            }
+        }
+        self.ranges = merged;
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    #[inline]
+    pub fn contains(&self, year: i32, month: u8, day: u8) -> bool {
+        self.contains_day(days_from_civil(year, month, day))
+    }
+
+    /// Whether a day (counted since the epoch) is excluded.
+    pub fn contains_day(&self, days: i64) -> bool {
+        let idx = self.ranges.partition_point(|&(_, end)| end < days);
+        self.ranges
+            .get(idx)
+            .is_some_and(|&(start, _)| start <= days)
+    }
+
+    /// First day at or after `days` that is not excluded.
+    pub fn next_open_day(&self, days: i64) -> i64 {
+        let idx = self.ranges.partition_point(|&(_, end)| end < days);
+        match self.ranges.get(idx) {
+            Some(&(start, end)) if start <= days => end + 1,
+            _ => days,
+        }
+    }
+}
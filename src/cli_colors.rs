@@ -7,6 +7,12 @@ pub struct CliColorPicker {
     next_color_idx: usize,
 }
 
+impl Default for CliColorPicker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl CliColorPicker {
     pub fn new() -> Self {
         const COLORS: [&str; 14] = [
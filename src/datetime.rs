@@ -0,0 +1,158 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Civil (proleptic Gregorian) date and time without any zone attached.
+/// Used as the "wall clock" the cron fields are matched against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DateTime {
+    pub year: i32,
+    pub month: u8,  // 1..12
+    pub day: u8,    // 1..31
+    pub hour: u8,   // 0..23
+    pub minute: u8, // 0..59
+    pub second: u8, // 0..59
+}
+
+impl DateTime {
+    pub fn new(year: i32, month: u8, day: u8, hour: u8, minute: u8, second: u8) -> Self {
+        Self {
+            year,
+            month,
+            day,
+            hour,
+            minute,
+            second,
+        }
+    }
+
+    /// Splits seconds since the Unix epoch into civil fields.
+    pub fn from_unix(secs: i64) -> Self {
+        let days = secs.div_euclid(86_400);
+        let rem = secs.rem_euclid(86_400);
+        let (year, month, day) = civil_from_days(days);
+
+        Self {
+            year,
+            month,
+            day,
+            hour: (rem / 3600) as u8,
+            minute: ((rem / 60) % 60) as u8,
+            second: (rem % 60) as u8,
+        }
+    }
+
+    /// Seconds since the Unix epoch, treating the fields as UTC.
+    pub fn to_unix(&self) -> i64 {
+        days_from_civil(self.year, self.month, self.day) * 86_400
+            + self.hour as i64 * 3600
+            + self.minute as i64 * 60
+            + self.second as i64
+    }
+
+    /// Parses `YYYY-MM-DD HH:MM[:SS]` (a `T` may replace the space).
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let err = || format!("Invalid date and time: {}", s);
+        let s = s.trim();
+        let (date, time) = s.split_once([' ', 'T']).ok_or_else(err)?;
+        let (year, month, day) = parse_date(date).map_err(|_| err())?;
+
+        let mut t = time.trim().splitn(3, ':');
+        let hour: u8 = t.next().and_then(|v| v.parse().ok()).ok_or_else(err)?;
+        let minute: u8 = t.next().and_then(|v| v.parse().ok()).ok_or_else(err)?;
+        let second: u8 = match t.next() {
+            Some(v) => v.parse().map_err(|_| err())?,
+            None => 0,
+        };
+
+        if hour > 23 || minute > 59 || second > 59 {
+            return Err(err());
+        }
+        Ok(Self::new(year, month, day, hour, minute, second))
+    }
+
+    /// Day of week, 0 = Sunday .. 6 = Saturday.
+    #[inline]
+    pub fn weekday(&self) -> u8 {
+        weekday_from_days(days_from_civil(self.year, self.month, self.day))
+    }
+}
+
+/// Parses a `YYYY-MM-DD` date.
+pub fn parse_date(s: &str) -> Result<(i32, u8, u8), String> {
+    let err = || format!("Invalid date: {}", s);
+    let mut d = s.trim().splitn(3, '-');
+    let year: i32 = d.next().and_then(|v| v.parse().ok()).ok_or_else(err)?;
+    let month: u8 = d.next().and_then(|v| v.parse().ok()).ok_or_else(err)?;
+    let day: u8 = d.next().and_then(|v| v.parse().ok()).ok_or_else(err)?;
+
+    if !(1..=12).contains(&month) || day == 0 || day > days_in_month(year, month) {
+        return Err(err());
+    }
+    Ok((year, month, day))
+}
+
+#[inline]
+pub fn is_leap_year(year: i32) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+#[inline]
+pub fn days_in_month(year: i32, month: u8) -> u8 {
+    match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if is_leap_year(year) => 29,
+        _ => 28,
+    }
+}
+
+/// Days since 1970-01-01 for a civil date (Howard Hinnant's algorithm).
+pub fn days_from_civil(year: i32, month: u8, day: u8) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year } as i64;
+    let m = month as i64;
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let mp = if m > 2 { m - 3 } else { m + 9 };
+    let doy = (153 * mp + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// Inverse of `days_from_civil`.
+pub fn civil_from_days(days: i64) -> (i32, u8, u8) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u8;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u8;
+    let year = (yoe + era * 400 + if month <= 2 { 1 } else { 0 }) as i32;
+    (year, month, day)
+}
+
+/// 1970-01-01 was a Thursday.
+#[inline]
+pub fn weekday_from_days(days: i64) -> u8 {
+    (days + 4).rem_euclid(7) as u8
+}
+
+#[inline]
+pub fn system_time_to_unix(t: SystemTime) -> i64 {
+    match t.duration_since(UNIX_EPOCH) {
+        Ok(d) => d.as_secs() as i64,
+        Err(e) => {
+            let d = e.duration();
+            -(d.as_secs() as i64) - if d.subsec_nanos() > 0 { 1 } else { 0 }
+        }
+    }
+}
+
+#[inline]
+pub fn unix_to_system_time(secs: i64) -> SystemTime {
+    if secs >= 0 {
+        UNIX_EPOCH + Duration::from_secs(secs as u64)
+    } else {
+        UNIX_EPOCH - Duration::from_secs(secs.unsigned_abs())
+    }
+}
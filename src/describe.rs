@@ -0,0 +1,287 @@
+use crate::datetime::DateTime;
+use crate::parser::{format_duration, CronSchedule, DayRule, Schedule, WeekdayRule};
+
+const MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+const WEEKDAYS: [&str; 7] = [
+    "Sunday",
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+];
+
+const NTH: [&str; 5] = ["first", "second", "third", "fourth", "fifth"];
+
+impl Schedule {
+    /// Plain-English summary of the schedule.
+    pub fn describe(&self) -> String {
+        match self {
+            Schedule::Cron(cron) => cron.describe(),
+            Schedule::Interval(interval) => {
+                let mut out = format!("every {}", format_duration(interval.every));
+                if let Some(anchor) = interval.anchor {
+                    out.push_str(&format!(
+                        ", aligned to {} UTC",
+                        format_datetime(&DateTime::from_unix(anchor))
+                    ));
+                }
+                out
+            }
+            Schedule::At(at) => format!("once at {}", format_datetime(at)),
+            Schedule::Reboot => "once when croner starts".to_string(),
+        }
+    }
+}
+
+impl CronSchedule {
+    /// Plain-English summary built from the parsed fields, e.g.
+    /// `*/15 9-17 * * 1-5` reads "every 15 minutes, between 09:00 and 17:59,
+    /// Monday through Friday".
+    pub fn describe(&self) -> String {
+        let mut parts = self.describe_time();
+
+        let days = self.describe_days();
+        let weekdays = self.describe_weekdays();
+        match (days, weekdays) {
+            (Some(d), Some(w)) if self.days_either() => parts.push(format!("{} or {}", d, w)),
+            (Some(d), Some(w)) => parts.push(format!("{} if it is {}", d, w)),
+            (Some(d), None) => parts.push(d),
+            (None, Some(w)) => parts.push(w),
+            (None, None) => {}
+        }
+
+        if self.months.len() < 12 {
+            let names: Vec<String> = self
+                .months
+                .iter()
+                .map(|&m| MONTHS[m as usize - 1].to_string())
+                .collect();
+            parts.push(format!("in {}", join_runs(&self.months, &names)));
+        }
+
+        if let Some(years) = &self.years {
+            let runs = runs_of(years.iter().map(|&y| y as i64));
+            let text: Vec<String> = runs
+                .iter()
+                .map(|&(a, b)| match b - a {
+                    0 => a.to_string(),
+                    1 => format!("{} and {}", a, b),
+                    _ => format!("{} through {}", a, b),
+                })
+                .collect();
+            parts.push(format!("in {}", join_and(&text)));
+        }
+
+        if !self.exclude.is_empty() {
+            let names: Vec<String> = self.exclude.iter().map(|c| c.name.clone()).collect();
+            let noun = if names.len() == 1 {
+                "calendar"
+            } else {
+                "calendars"
+            };
+            parts.push(format!("excluding {} {}", noun, join_and(&names)));
+        }
+
+        parts.join(", ")
+    }
+
+    fn describe_time(&self) -> Vec<String> {
+        let s_zero = self.seconds == [0];
+        let m_all = self.minutes.len() == 60;
+        let h_all = self.hours.len() == 24;
+
+        // A handful of fixed times reads best as clock times
+        if self.seconds.len() == 1 && self.minutes.len() == 1 && !h_all && self.hours.len() <= 4 {
+            let (s, m) = (self.seconds[0], self.minutes[0]);
+            let times: Vec<String> = self
+                .hours
+                .iter()
+                .map(|&h| match s {
+                    0 => format!("{:02}:{:02}", h, m),
+                    _ => format!("{:02}:{:02}:{:02}", h, m, s),
+                })
+                .collect();
+            return vec![format!("at {}", join_and(&times))];
+        }
+
+        let mut parts = Vec::new();
+        if !s_zero {
+            parts.push(describe_unit(&self.seconds, 59, "second"));
+        }
+
+        // Every minute goes without saying once seconds are spelled out
+        if !m_all || s_zero {
+            if self.minutes.len() == 1 && h_all {
+                parts.push(format!("at minute {} of every hour", self.minutes[0]));
+            } else {
+                parts.push(describe_unit(&self.minutes, 59, "minute"));
+            }
+        }
+
+        if !h_all {
+            parts.push(describe_hours(&self.hours));
+        }
+        parts
+    }
+
+    fn describe_days(&self) -> Option<String> {
+        if !self.day_restricted {
+            return None;
+        }
+
+        let mut items = Vec::new();
+        match self.days.len() {
+            0 => {}
+            1 => items.push(format!("day {}", self.days[0])),
+            _ => {
+                let names: Vec<String> = self.days.iter().map(|d| d.to_string()).collect();
+                items.push(format!("days {}", join_runs(&self.days, &names)));
+            }
+        }
+        for rule in &self.day_rules {
+            items.push(match *rule {
+                DayRule::Last(0) => "the last day".to_string(),
+                DayRule::Last(n) => format!("{} days before the last day", n),
+                DayRule::LastWeekday => "the last weekday".to_string(),
+                DayRule::NearestWeekday(d) => format!("the weekday nearest day {}", d),
+            });
+        }
+        Some(format!("on {} of the month", join_and(&items)))
+    }
+
+    fn describe_weekdays(&self) -> Option<String> {
+        if !self.weekday_restricted {
+            return None;
+        }
+
+        let mut items = Vec::new();
+        if !self.weekdays.is_empty() {
+            let names: Vec<String> = self
+                .weekdays
+                .iter()
+                .map(|&w| WEEKDAYS[w as usize].to_string())
+                .collect();
+            items.push(join_runs(&self.weekdays, &names));
+        }
+        for rule in &self.weekday_rules {
+            items.push(match *rule {
+                WeekdayRule::Last(w) => {
+                    format!("on the last {} of the month", WEEKDAYS[w as usize])
+                }
+                WeekdayRule::Nth(w, n) => format!(
+                    "on the {} {} of the month",
+                    NTH[n as usize - 1],
+                    WEEKDAYS[w as usize]
+                ),
+            });
+        }
+        Some(join_and(&items))
+    }
+}
+
+/// Seconds or minutes: `every 15 minutes`, `at minutes 0 and 30`, ...
+fn describe_unit(values: &[u8], max: u8, unit: &str) -> String {
+    if values.len() == max as usize + 1 {
+        return format!("every {}", unit);
+    }
+    if values.len() == 1 {
+        return format!("at {} {}", unit, values[0]);
+    }
+    if let Some((start, step)) = progression(values, max) {
+        return match start {
+            0 => format!("every {} {}s", step, unit),
+            _ => format!("every {} {}s starting at {} {}", step, unit, unit, start),
+        };
+    }
+    let names: Vec<String> = values.iter().map(|v| v.to_string()).collect();
+    format!("at {}s {}", unit, join_runs(values, &names))
+}
+
+fn describe_hours(hours: &[u8]) -> String {
+    let runs = runs_of(hours.iter().map(|&h| h as i64));
+    if runs.len() == 1 {
+        let (a, b) = runs[0];
+        return format!("between {:02}:00 and {:02}:59", a, b);
+    }
+    if let Some((start, step)) = progression(hours, 23) {
+        return match start {
+            0 => format!("every {} hours", step),
+            _ => format!("every {} hours starting at {:02}:00", step, start),
+        };
+    }
+    let names: Vec<String> = hours.iter().map(|h| format!("{:02}", h)).collect();
+    format!("during hours {}", join_runs(hours, &names))
+}
+
+/// `(start, step)` if `values` is `start, start+step, ...` up to the end of
+/// the field, as `start/step` would produce.
+fn progression(values: &[u8], max: u8) -> Option<(u8, u8)> {
+    if values.len() < 2 {
+        return None;
+    }
+    let step = values[1] - values[0];
+    let regular = values.windows(2).all(|w| w[1] - w[0] == step);
+    let reaches_end = *values.last()? as u16 + step as u16 > max as u16;
+    (step > 1 && regular && reaches_end).then_some((values[0], step))
+}
+
+/// Contiguous runs of sorted values as inclusive `(first, last)` pairs.
+fn runs_of(values: impl Iterator<Item = i64>) -> Vec<(i64, i64)> {
+    let mut runs: Vec<(i64, i64)> = Vec::new();
+    for v in values {
+        match runs.last_mut() {
+            Some(last) if v == last.1 + 1 => last.1 = v,
+            _ => runs.push((v, v)),
+        }
+    }
+    runs
+}
+
+/// Names for sorted values, with runs of three or more collapsed into
+/// `first through last`.
+fn join_runs(values: &[u8], names: &[String]) -> String {
+    let mut items = Vec::new();
+    let mut i = 0;
+    for (a, b) in runs_of(values.iter().map(|&v| v as i64)) {
+        let len = (b - a + 1) as usize;
+        if len >= 3 {
+            items.push(format!("{} through {}", names[i], names[i + len - 1]));
+        } else {
+            items.extend(names[i..i + len].iter().cloned());
+        }
+        i += len;
+    }
+    join_and(&items)
+}
+
+/// `a`, `a and b`, `a, b and c`.
+fn join_and(items: &[String]) -> String {
+    match items {
+        [] => String::new(),
+        [one] => one.clone(),
+        [rest @ .., last] => format!("{} and {}", rest.join(", "), last),
+    }
+}
+
+fn format_datetime(dt: &DateTime) -> String {
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        dt.year, dt.month, dt.day, dt.hour, dt.minute, dt.second
+    )
+}
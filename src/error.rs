@@ -0,0 +1,394 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::ops::Range;
+
+use crate::parser::{MAX_YEAR, MIN_YEAR};
+
+/// A field of a cron expression.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    Second,
+    Minute,
+    Hour,
+    Day,
+    Month,
+    Weekday,
+    Year,
+}
+
+impl Field {
+    /// Smallest and largest value the field accepts.
+    pub fn bounds(self) -> (i32, i32) {
+        match self {
+            Field::Second | Field::Minute => (0, 59),
+            Field::Hour => (0, 23),
+            Field::Day => (1, 31),
+            Field::Month => (1, 12),
+            Field::Weekday => (0, 7),
+            Field::Year => (MIN_YEAR, MAX_YEAR),
+        }
+    }
+}
+
+impl fmt::Display for Field {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Field::Second => "second",
+            Field::Minute => "minute",
+            Field::Hour => "hour",
+            Field::Day => "day of month",
+            Field::Month => "month",
+            Field::Weekday => "day of week",
+            Field::Year => "year",
+        })
+    }
+}
+
+/// Why a schedule failed to parse. `span` is the byte range of the
+/// offending text within the string given to the parser.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CronError {
+    UnknownMacro {
+        name: String,
+        span: Range<usize>,
+    },
+    FieldCount {
+        found: usize,
+        span: Range<usize>,
+    },
+    InvalidValue {
+        field: Field,
+        value: String,
+        span: Range<usize>,
+    },
+    OutOfRange {
+        field: Field,
+        value: String,
+        span: Range<usize>,
+    },
+    InvalidRange {
+        field: Field,
+        value: String,
+        span: Range<usize>,
+    },
+    InvalidStep {
+        field: Field,
+        value: String,
+        span: Range<usize>,
+    },
+    InvalidDuration {
+        value: String,
+        span: Range<usize>,
+    },
+    InvalidDateTime {
+        value: String,
+        span: Range<usize>,
+    },
+}
+
+impl CronError {
+    pub fn span(&self) -> Range<usize> {
+        match self {
+            CronError::UnknownMacro { span, .. }
+            | CronError::FieldCount { span, .. }
+            | CronError::InvalidValue { span, .. }
+            | CronError::OutOfRange { span, .. }
+            | CronError::InvalidRange { span, .. }
+            | CronError::InvalidStep { span, .. }
+            | CronError::InvalidDuration { span, .. }
+            | CronError::InvalidDateTime { span, .. } => span.clone(),
+        }
+    }
+
+    /// The message followed by `source`, the parsed expression, with the
+    /// offending part underlined.
+    pub fn render(&self, source: &str) -> String {
+        let span = self.span();
+        match (source.get(..span.start), source.get(span)) {
+            (Some(before), Some(bad)) => snippet(
+                &self.to_string(),
+                None,
+                source,
+                before.chars().count(),
+                bad.chars().count(),
+            ),
+            _ => self.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for CronError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CronError::UnknownMacro { name, .. } => write!(f, "Unknown macro: {}", name),
+            CronError::FieldCount { found, .. } => write!(
+                f,
+                "Expected 5, 6 or 7 fields in cron expression, got: {}",
+                found
+            ),
+            CronError::InvalidValue { value, .. } => write!(f, "Invalid value: {}", value),
+            CronError::OutOfRange { field, value, .. } => {
+                let (lo, hi) = field.bounds();
+                write!(
+                    f,
+                    "Invalid value: {} ({} must be {}-{})",
+                    value, field, lo, hi
+                )
+            }
+            CronError::InvalidRange { value, .. } => write!(f, "Invalid range: {}", value),
+            CronError::InvalidStep { value, .. } => write!(f, "Invalid step: {}", value),
+            CronError::InvalidDuration { value, .. } => write!(f, "Invalid duration: {}", value),
+            CronError::InvalidDateTime { value, .. } => {
+                write!(f, "Invalid date and time: {}", value)
+            }
+        }
+    }
+}
+
+impl Error for CronError {}
+
+/// Why a config file was rejected. `line` and `col` are 1-based and `col`
+/// and `len` count characters.
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(io::Error),
+    NotUtf8,
+    ExpectedKeyValue {
+        line: usize,
+    },
+    DuplicateSection {
+        line: usize,
+        name: String,
+    },
+    InvalidSection {
+        line: usize,
+        header: String,
+    },
+    UnknownKey {
+        line: usize,
+        col: usize,
+        key: String,
+    },
+    DuplicateKey {
+        line: usize,
+        col: usize,
+        key: String,
+    },
+    KeyOutsideJob {
+        line: usize,
+        col: usize,
+        key: String,
+    },
+    InvalidValue {
+        line: usize,
+        col: usize,
+        len: usize,
+        key: String,
+        message: String,
+    },
+    /// `col` is where the schedule value starts; the error's span is
+    /// relative to it.
+    InvalidSchedule {
+        line: usize,
+        col: usize,
+        id: String,
+        error: CronError,
+    },
+    MissingKey {
+        line: usize,
+        id: String,
+        key: &'static str,
+    },
+    Job {
+        line: usize,
+        id: String,
+        message: String,
+    },
+    DuplicateJobId {
+        line: usize,
+        id: String,
+    },
+}
+
+impl ConfigError {
+    /// Line the error was found on, if it comes from the file's contents.
+    pub fn line(&self) -> Option<usize> {
+        match self {
+            ConfigError::Io(_) | ConfigError::NotUtf8 => None,
+            ConfigError::ExpectedKeyValue { line }
+            | ConfigError::DuplicateSection { line, .. }
+            | ConfigError::InvalidSection { line, .. }
+            | ConfigError::UnknownKey { line, .. }
+            | ConfigError::DuplicateKey { line, .. }
+            | ConfigError::KeyOutsideJob { line, .. }
+            | ConfigError::InvalidValue { line, .. }
+            | ConfigError::InvalidSchedule { line, .. }
+            | ConfigError::MissingKey { line, .. }
+            | ConfigError::Job { line, .. }
+            | ConfigError::DuplicateJobId { line, .. } => Some(*line),
+        }
+    }
+
+    /// The message followed by the offending line of `source`, the config
+    /// text, with the bad part underlined when its column is known.
+    pub fn render(&self, source: &str) -> String {
+        let message = self.to_string();
+        let Some(line) = self.line() else {
+            return message;
+        };
+        let source = source.strip_prefix('\u{feff}').unwrap_or(source);
+        let Some(text) = source.lines().nth(line - 1) else {
+            return message;
+        };
+
+        let (col, len) = match self {
+            ConfigError::UnknownKey { col, key, .. }
+            | ConfigError::DuplicateKey { col, key, .. }
+            | ConfigError::KeyOutsideJob { col, key, .. } => (*col, key.chars().count()),
+            ConfigError::InvalidValue { col, len, .. } => (*col, *len),
+            ConfigError::InvalidSchedule { col, error, .. } => {
+                let value: String = text.chars().skip(col - 1).collect();
+                let span = error.span();
+                match (value.get(..span.start), value.get(span)) {
+                    (Some(before), Some(bad)) => {
+                        (col + before.chars().count(), bad.chars().count())
+                    }
+                    _ => (*col, 1),
+                }
+            }
+            _ => return snippet(&message, Some(line), text, 0, 0),
+        };
+        snippet(&message, Some(line), text, col - 1, len)
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io(e) => write!(f, "failed to read config: {}", e),
+            ConfigError::NotUtf8 => f.write_str("config is not valid UTF-8"),
+            ConfigError::ExpectedKeyValue { line } => {
+                write!(f, "line {}: expected `key = value`", line)
+            }
+            ConfigError::DuplicateSection { line, name } => {
+                write!(f, "line {}: duplicate [{}] section", line, name)
+            }
+            ConfigError::InvalidSection { line, header } => {
+                write!(f, "line {}: invalid section header {}", line, header)
+            }
+            ConfigError::UnknownKey { line, key, .. } => {
+                write!(f, "line {}: unknown key {}", line, key)
+            }
+            ConfigError::DuplicateKey { line, key, .. } => {
+                write!(f, "line {}: duplicate `{}`", line, key)
+            }
+            ConfigError::KeyOutsideJob { line, .. } => {
+                write!(f, "line {}: key outside of [job:<id>] section", line)
+            }
+            ConfigError::InvalidValue { line, message, .. } => {
+                write!(f, "line {}: {}", line, message)
+            }
+            ConfigError::InvalidSchedule {
+                line, id, error, ..
+            } => write!(
+                f,
+                "line {}: job '{}': invalid schedule: {}",
+                line, id, error
+            ),
+            ConfigError::MissingKey { line, id, key } => {
+                write!(f, "line {}: job '{}': missing {}", line, id, key)
+            }
+            ConfigError::Job { line, id, message } => {
+                write!(f, "line {}: job '{}': {}", line, id, message)
+            }
+            ConfigError::DuplicateJobId { line, id } => {
+                write!(f, "line {}: duplicate job id '{}'", line, id)
+            }
+        }
+    }
+}
+
+impl Error for ConfigError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ConfigError::Io(e) => Some(e),
+            ConfigError::InvalidSchedule { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ConfigError {
+    fn from(e: io::Error) -> Self {
+        ConfigError::Io(e)
+    }
+}
+
+/// Every problem found in a config file, in line order. Never empty.
+#[derive(Debug)]
+pub struct ConfigErrors {
+    errors: Vec<ConfigError>,
+}
+
+impl ConfigErrors {
+    pub fn new(errors: Vec<ConfigError>) -> Self {
+        debug_assert!(!errors.is_empty());
+        Self { errors }
+    }
+
+    pub fn errors(&self) -> &[ConfigError] {
+        &self.errors
+    }
+
+    /// Each error rendered against `source`, separated by blank lines.
+    pub fn render(&self, source: &str) -> String {
+        let rendered: Vec<String> = self.errors.iter().map(|e| e.render(source)).collect();
+        rendered.join("\n\n")
+    }
+}
+
+/// One error per line.
+impl fmt::Display for ConfigErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, e) in self.errors.iter().enumerate() {
+            if i > 0 {
+                f.write_str("\n")?;
+            }
+            write!(f, "{}", e)?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for ConfigErrors {}
+
+impl From<ConfigError> for ConfigErrors {
+    fn from(e: ConfigError) -> Self {
+        Self::new(vec![e])
+    }
+}
+
+impl From<io::Error> for ConfigErrors {
+    fn from(e: io::Error) -> Self {
+        ConfigError::Io(e).into()
+    }
+}
+
+/// `message`, then `text` with `len` characters from 0-based `col`
+/// underlined. Without carets when `len` is 0.
+fn snippet(message: &str, line: Option<usize>, text: &str, col: usize, len: usize) -> String {
+    let number = line.map(|n| n.to_string()).unwrap_or_default();
+    let pad = " ".repeat(number.len());
+    let mut out = format!("{}\n{} |\n{} | {}", message, pad, number, text);
+    if len > 0 {
+        // Keep tabs so the carets line up with the text above them
+        let indent: String = text
+            .chars()
+            .take(col)
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+        out.push_str(&format!("\n{} | {}{}", pad, indent, "^".repeat(len)));
+    }
+    out
+}
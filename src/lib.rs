@@ -1,7 +1,14 @@
+pub mod calendar;
 pub mod cli_colors;
+pub mod datetime;
+pub mod describe;
+pub mod error;
 pub mod loader;
 pub mod models;
 pub mod parser;
 pub mod printer;
 pub mod scheduler;
 pub mod shell;
+pub mod state;
+pub mod supervisor;
+pub mod tz;
@@ -1,49 +1,80 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::ffi::OsString;
 use std::fs;
 use std::path::Path;
-use std::time::SystemTime;
-
-use crate::models::{Fanout, JobSpec};
-use crate::parser::CronParser;
-use crate::shell::get_command_as_os_str;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use crate::calendar::Calendar;
+use crate::datetime::{unix_to_system_time, DateTime};
+use crate::error::{ConfigError, ConfigErrors};
+use crate::models::{Fanout, JobSpec, Overlap, DEFAULT_KILL_GRACE, MAX_JITTER};
+use crate::parser::{
+    parse_duration, parse_duration_or_zero, CronParser, DayMatch, DstPolicy, Schedule,
+};
+use crate::scheduler::hash_id;
+use crate::shell::{try_get_command_as_os_str, Shell};
+use crate::tz::TimeZone;
 
 pub struct ConfigCache {
     pub jobs: Vec<JobSpec>,
+    /// Most runs the scheduler keeps going at once, across all jobs.
+    pub max_concurrent: Option<usize>,
     last_modified: Option<SystemTime>,
     file_size: Option<u64>,
+    /// Set once a reload fails, to the mtime and size of the file it failed
+    /// on (`None` if the file couldn't be read at all).
+    failed: Option<Option<(SystemTime, u64)>>,
+}
+
+impl Default for ConfigCache {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl ConfigCache {
     pub fn new() -> Self {
         Self {
             jobs: Vec::new(),
+            max_concurrent: None,
             last_modified: None,
             file_size: None,
+            failed: None,
         }
     }
 
     /// Atomically reloads config if the file changed (mtime+size).
-    /// Returns true if reloaded, false if unchanged.
-    pub fn reload_if_changed(&mut self, path: &Path) -> Result<bool, String> {
-        let file = match fs::File::open(path) {
-            Ok(f) => f,
-            Err(e) => return Err(format!("config open error: {}", e)),
-        };
-        let meta = match file.metadata() {
-            Ok(m) => m,
-            Err(e) => return Err(format!("config metadata error: {}", e)),
-        };
-        let modified = meta.modified().unwrap_or(SystemTime::UNIX_EPOCH);
-        let size = meta.len();
-
-        if self.last_modified == Some(modified) && self.file_size == Some(size) {
+    /// Returns true if reloaded, false if unchanged. A file that failed to
+    /// load is reported once and not read again until it changes.
+    pub fn reload_if_changed(&mut self, path: &Path) -> Result<bool, ConfigErrors> {
+        let meta = fs::File::open(path).and_then(|f| f.metadata());
+        let stamp = meta.as_ref().ok().map(|meta| {
+            let modified = meta.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+            (modified, meta.len())
+        });
+
+        if let Some((modified, size)) = stamp {
+            if self.last_modified == Some(modified) && self.file_size == Some(size) {
+                return Ok(false);
+            }
+        }
+        if self.failed == Some(stamp) {
             return Ok(false);
         }
 
-        let new_jobs = load_config(path)?;
-        self.jobs = new_jobs;
-        self.last_modified = Some(modified);
-        self.file_size = Some(size);
+        let config = match meta.map_err(ConfigErrors::from).and_then(|_| load(path)) {
+            Ok(config) => config,
+            Err(e) => {
+                self.failed = Some(stamp);
+                return Err(e);
+            }
+        };
+        self.jobs = config.jobs;
+        self.max_concurrent = config.max_concurrent;
+        self.last_modified = stamp.map(|(modified, _)| modified);
+        self.file_size = stamp.map(|(_, size)| size);
+        self.failed = None;
         Ok(true)
     }
 }
@@ -55,34 +86,62 @@ struct JobBuilder<'a> {
     command: Option<&'a str>,
     fanout_int: Option<usize>,
     fanout_list: Vec<String>,
+    timezone: Option<Arc<TimeZone>>,
+    dst_policy: Option<DstPolicy>,
+    day_match: Option<DayMatch>,
+    anchor: Option<&'a str>,
+    jitter: Option<Duration>,
+    timeout: Option<Duration>,
+    kill_grace: Option<Duration>,
+    overlap: Option<Overlap>,
+    max_parallel: Option<usize>,
+    not_before: Option<&'a str>,
+    not_after: Option<&'a str>,
+    exclude: Vec<String>,
+    shell: Option<Shell>,
+    rejected: HashSet<&'a [u8]>,
     first_line: usize,
 }
 
-pub fn load_config(path: &Path) -> Result<Vec<JobSpec>, String> {
-    let bytes = match fs::read(path) {
-        Ok(b) => b,
-        Err(e) => return Err(format!("failed to read config: {}", e)),
-    };
+// Global defaults from the [settings] section
+#[derive(Default)]
+struct Settings {
+    timezone: Option<Arc<TimeZone>>,
+    dst_policy: Option<DstPolicy>,
+    day_match: Option<DayMatch>,
+    max_concurrent: Option<usize>,
+}
+
+/// Everything a config file defines.
+#[derive(Debug)]
+pub struct Config {
+    pub jobs: Vec<JobSpec>,
+    /// Most runs the scheduler keeps going at once, across all jobs.
+    pub max_concurrent: Option<usize>,
+}
+
+pub fn load_config(path: &Path) -> Result<Vec<JobSpec>, ConfigErrors> {
+    load(path).map(|config| config.jobs)
+}
+
+/// Like `load_config`, but with the global settings the scheduler needs.
+pub fn load(path: &Path) -> Result<Config, ConfigErrors> {
+    let bytes = fs::read(path)?;
 
     let text = match std::str::from_utf8(&bytes) {
         Ok(t) => t,
-        Err(_) => return Err("config is not valid UTF-8".to_string()),
+        Err(_) => return Err(ConfigError::NotUtf8.into()),
     };
 
-    let mut jobs: Vec<JobSpec> = Vec::new();
-    let mut seen_ids: HashSet<String> = HashSet::new();
-    let mut cur: Option<JobBuilder> = None;
-    let mut cron = CronParser::new();
+    // Skip UTF-8 BOM if present
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
 
     let data = text.as_bytes();
     let n = data.len();
     let mut i = 0usize;
     let mut lineno = 0usize;
-
-    // Skip UTF-8 BOM if present
-    if n >= 3 && data[0..3] == [0xEF, 0xBB, 0xBF] {
-        i = 3;
-    }
+    let mut reader = Reader::new(data);
+    let mut errors = Vec::new();
 
     while i < n {
         lineno += 1;
@@ -104,7 +163,7 @@ pub fn load_config(path: &Path) -> Result<Vec<JobSpec>, String> {
         let mut line = &data[line_start..line_end];
 
         // Strip comment
-        if let Some(hash) = memchr(line, b'#') {
+        if let Some(hash) = comment_start(line) {
             line = &line[..hash];
         }
 
@@ -113,139 +172,486 @@ pub fn load_config(path: &Path) -> Result<Vec<JobSpec>, String> {
             continue;
         }
 
+        // Keep going after a bad line so every problem is reported at once
+        if let Err(e) = reader.line(lineno, line) {
+            errors.push(e);
+        }
+    }
+
+    let Reader {
+        builders,
+        mut settings,
+        calendars,
+        ..
+    } = reader;
+
+    // Jobs are finalized once the whole file is read so [settings] may
+    // appear anywhere and still provide defaults.
+    if settings.timezone.is_none() {
+        settings.timezone = Some(Arc::new(TimeZone::local()));
+    }
+
+    let calendars: HashMap<&str, Arc<Calendar>> = calendars
+        .into_iter()
+        .map(|(name, c)| (name, Arc::new(c)))
+        .collect();
+
+    let mut jobs: Vec<JobSpec> = Vec::with_capacity(builders.len());
+    let mut seen_ids: HashSet<&str> = HashSet::new();
+    let mut cron = CronParser::new();
+
+    for b in builders {
+        if !seen_ids.insert(b.id) {
+            errors.push(ConfigError::DuplicateJobId {
+                line: b.first_line,
+                id: b.id.to_string(),
+            });
+            continue;
+        }
+        if let Some(job) = finalize_job(&mut cron, b, &settings, &calendars, data, &mut errors) {
+            jobs.push(job);
+        }
+    }
+
+    // A config with any error is rejected as a whole
+    if !errors.is_empty() {
+        errors.sort_by_key(ConfigError::line);
+        return Err(ConfigErrors::new(errors));
+    }
+    Ok(Config {
+        jobs,
+        max_concurrent: settings.max_concurrent,
+    })
+}
+
+// Section the reader is currently in
+#[derive(Clone, Copy)]
+enum Section<'a> {
+    None,
+    Settings,
+    Calendar(&'a str),
+    Job,
+    // A rejected header; its keys are ignored rather than misattributed
+    Skipped,
+}
+
+// Line-by-line state while reading a config
+struct Reader<'a> {
+    data: &'a [u8],
+    section: Section<'a>,
+    builders: Vec<JobBuilder<'a>>,
+    settings: Settings,
+    seen_settings: bool,
+    calendars: HashMap<&'a str, Calendar>,
+    zones: HashMap<&'a str, Arc<TimeZone>>,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self {
+            data,
+            section: Section::None,
+            builders: Vec::new(),
+            settings: Settings::default(),
+            seen_settings: false,
+            calendars: HashMap::new(),
+            zones: HashMap::new(),
+        }
+    }
+
+    /// Handles one trimmed, non-empty line.
+    fn line(&mut self, lineno: usize, line: &'a [u8]) -> Result<(), ConfigError> {
         // Section header
-        if let Some(id_slice) = parse_section_header(line) {
-            if let Some(prev) = cur.take() {
-                let start_line = prev.first_line;
-                let job = match finalize_job(&mut cron, prev) {
-                    Ok(j) => j,
-                    Err(e) => return Err(format!("line {}: {}", start_line, e)),
-                };
+        if line == b"[settings]" {
+            if self.seen_settings {
+                self.section = Section::Skipped;
+                return Err(ConfigError::DuplicateSection {
+                    line: lineno,
+                    name: "settings".into(),
+                });
+            }
+            self.seen_settings = true;
+            self.section = Section::Settings;
+            return Ok(());
+        }
 
-                if !seen_ids.insert(job.id.clone()) {
-                    return Err(format!("duplicate job id '{}'", job.id));
-                }
-                jobs.push(job);
+        if let Some(name) = parse_section_header(line, b"[calendar:") {
+            if self.calendars.contains_key(name) {
+                self.section = Section::Skipped;
+                return Err(ConfigError::DuplicateSection {
+                    line: lineno,
+                    name: format!("calendar:{}", name),
+                });
             }
+            self.calendars.insert(name, Calendar::new(name));
+            self.section = Section::Calendar(name);
+            return Ok(());
+        }
 
-            cur = Some(JobBuilder {
-                id: id_slice,
+        if let Some(id) = parse_section_header(line, b"[job:") {
+            self.builders.push(JobBuilder {
+                id,
                 schedule: None,
                 command: None,
                 fanout_int: None,
                 fanout_list: Vec::new(),
+                timezone: None,
+                dst_policy: None,
+                day_match: None,
+                anchor: None,
+                jitter: None,
+                timeout: None,
+                kill_grace: None,
+                overlap: None,
+                max_parallel: None,
+                not_before: None,
+                not_after: None,
+                exclude: Vec::new(),
+                shell: None,
+                rejected: HashSet::new(),
                 first_line: lineno,
             });
+            self.section = Section::Job;
+            return Ok(());
+        }
+
+        if line.starts_with(b"[") && line.ends_with(b"]") {
+            self.section = Section::Skipped;
+            return Err(ConfigError::InvalidSection {
+                line: lineno,
+                header: as_debug_str(line),
+            });
+        }
 
-            continue;
+        if let Section::Skipped = self.section {
+            return Ok(());
         }
 
         // key = value
         let (key, value) = match parse_key_value(line) {
             Some(kv) => kv,
-            None => return Err(format!("line {}: expected `key = value`", lineno)),
+            None => return Err(ConfigError::ExpectedKeyValue { line: lineno }),
         };
 
-        let Some(b) = cur.as_mut() else {
-            return Err(format!(
-                "line {}: key outside of [job:<id>] section",
-                lineno
-            ));
-        };
+        match self.section {
+            Section::Settings => self.settings_key(key, value),
+            Section::Calendar(name) => self.calendar_key(name, key, value),
+            Section::Job => {
+                let result = self.job_key(key, value);
+                if result.is_err() {
+                    // Not reported again as missing when the job is finalized
+                    let b = self.builders.last_mut().expect("job section");
+                    b.rejected.insert(key);
+                }
+                result
+            }
+            Section::None | Section::Skipped => {
+                let (line, col) = locate(self.data, key);
+                Err(ConfigError::KeyOutsideJob {
+                    line,
+                    col,
+                    key: as_debug_str(key),
+                })
+            }
+        }
+    }
 
+    fn settings_key(&mut self, key: &'a [u8], value: &'a [u8]) -> Result<(), ConfigError> {
+        let data = self.data;
+        let settings = &mut self.settings;
+        match key {
+            b"timezone" => {
+                if settings.timezone.is_some() {
+                    return Err(duplicate_key(data, key));
+                }
+                let zone = parse_timezone(&mut self.zones, value)
+                    .map_err(|e| invalid_value(data, key, value, e))?;
+                settings.timezone = Some(zone);
+            }
+            b"dst_policy" => {
+                if settings.dst_policy.is_some() {
+                    return Err(duplicate_key(data, key));
+                }
+                let policy =
+                    parse_dst_policy(value).map_err(|e| invalid_value(data, key, value, e))?;
+                settings.dst_policy = Some(policy);
+            }
+            b"day_match" => {
+                if settings.day_match.is_some() {
+                    return Err(duplicate_key(data, key));
+                }
+                let mode =
+                    parse_day_match(value).map_err(|e| invalid_value(data, key, value, e))?;
+                settings.day_match = Some(mode);
+            }
+            b"max_concurrent" => {
+                if settings.max_concurrent.is_some() {
+                    return Err(duplicate_key(data, key));
+                }
+                let max =
+                    parse_limit(key, value).map_err(|e| invalid_value(data, key, value, e))?;
+                settings.max_concurrent = Some(max);
+            }
+            _ => return Err(unknown_key(data, key)),
+        }
+        Ok(())
+    }
+
+    fn calendar_key(&mut self, name: &str, key: &[u8], value: &[u8]) -> Result<(), ConfigError> {
+        let data = self.data;
+        let calendar = self.calendars.get_mut(name).expect("calendar registered");
+        match key {
+            b"dates" => {
+                let s = std::str::from_utf8(value)
+                    .map_err(|_| invalid_value(data, key, value, "invalid UTF-8 in dates"))?;
+                let items = parse_name_list(s).map_err(|e| invalid_value(data, key, value, e))?;
+                for item in items {
+                    calendar
+                        .add(&item)
+                        .map_err(|e| invalid_value(data, key, value, e))?;
+                }
+            }
+            _ => return Err(unknown_key(data, key)),
+        }
+        Ok(())
+    }
+
+    fn job_key(&mut self, key: &'a [u8], value: &'a [u8]) -> Result<(), ConfigError> {
+        let data = self.data;
+        let b = self.builders.last_mut().expect("job section");
         match key {
             b"schedule" => {
                 if b.schedule.is_some() {
-                    return Err(format!("line {}: duplicate `schedule`", lineno));
+                    return Err(duplicate_key(data, key));
                 }
 
                 let v = trim_ascii(value);
-                let s = match std::str::from_utf8(v) {
-                    Ok(s) => s,
-                    Err(_) => return Err(format!("line {}: invalid UTF-8 in schedule", lineno)),
-                };
+                let s = std::str::from_utf8(v)
+                    .map_err(|_| invalid_value(data, key, value, "invalid UTF-8 in schedule"))?;
 
                 b.schedule = Some(s);
             }
             b"command" => {
                 if b.command.is_some() {
-                    return Err(format!("line {}: duplicate `command`", lineno));
+                    return Err(duplicate_key(data, key));
                 }
 
                 let v = trim_ascii(value);
 
                 if v.is_empty() {
-                    return Err(format!("line {}: command cannot be empty", lineno));
+                    return Err(invalid_value(data, key, value, "command cannot be empty"));
                 }
 
-                let s = match std::str::from_utf8(v) {
-                    Ok(s) => s,
-                    Err(_) => return Err(format!("line {}: invalid UTF-8 in command", lineno)),
-                };
+                let s = std::str::from_utf8(v)
+                    .map_err(|_| invalid_value(data, key, value, "invalid UTF-8 in command"))?;
 
                 b.command = Some(s);
             }
             b"fanout" => {
                 if !b.fanout_list.is_empty() {
-                    return Err(format!(
-                        "line {}: `fanout` conflicts with `fanout[]`",
-                        lineno
+                    return Err(invalid_value(
+                        data,
+                        key,
+                        value,
+                        "`fanout` conflicts with `fanout[]`",
                     ));
                 }
                 if b.fanout_int.is_some() {
-                    return Err(format!("line {}: duplicate `fanout`", lineno));
+                    return Err(duplicate_key(data, key));
                 }
 
-                let s = match std::str::from_utf8(trim_ascii(value)) {
-                    Ok(s) => s,
-                    Err(_) => return Err(format!("line {}: invalid UTF-8 in fanout", lineno)),
-                };
+                let s = std::str::from_utf8(trim_ascii(value))
+                    .map_err(|_| invalid_value(data, key, value, "invalid UTF-8 in fanout"))?;
 
                 let s_trim = s.trim();
                 if s_trim.starts_with('[') && s_trim.ends_with(']') {
                     // New list syntax: fanout = [ ... ]
                     let inner = &s_trim[1..s_trim.len() - 1];
                     let items =
-                        parse_list_items(inner).map_err(|e| format!("line {}: {}", lineno, e))?;
+                        parse_list_items(inner).map_err(|e| invalid_value(data, key, value, e))?;
                     b.fanout_list.extend(items);
                 } else {
                     // Fallback: old single integer syntax
-                    let n: usize = match s_trim.parse() {
-                        Ok(n) => n,
-                        Err(_) => {
-                            return Err(format!(
-                                "line {}: fanout must be an integer or list",
-                                lineno
-                            ))
-                        }
-                    };
+                    let n: usize = s_trim.parse().map_err(|_| {
+                        invalid_value(data, key, value, "fanout must be an integer or list")
+                    })?;
                     b.fanout_int = Some(n);
                 }
             }
-            _ => {
-                return Err(format!(
-                    "line {}: unknown key {}",
-                    lineno,
-                    as_debug_str(key)
-                ));
+            b"timezone" => {
+                if b.timezone.is_some() {
+                    return Err(duplicate_key(data, key));
+                }
+                let zone = parse_timezone(&mut self.zones, value)
+                    .map_err(|e| invalid_value(data, key, value, e))?;
+                b.timezone = Some(zone);
+            }
+            b"dst_policy" => {
+                if b.dst_policy.is_some() {
+                    return Err(duplicate_key(data, key));
+                }
+                let policy =
+                    parse_dst_policy(value).map_err(|e| invalid_value(data, key, value, e))?;
+                b.dst_policy = Some(policy);
+            }
+            b"day_match" => {
+                if b.day_match.is_some() {
+                    return Err(duplicate_key(data, key));
+                }
+                let mode =
+                    parse_day_match(value).map_err(|e| invalid_value(data, key, value, e))?;
+                b.day_match = Some(mode);
+            }
+            b"anchor" => {
+                if b.anchor.is_some() {
+                    return Err(duplicate_key(data, key));
+                }
+                let s = std::str::from_utf8(trim_ascii(value))
+                    .map_err(|_| invalid_value(data, key, value, "invalid UTF-8 in anchor"))?;
+                b.anchor = Some(s);
+            }
+            b"jitter" | b"timeout" | b"kill_grace" => {
+                let slot = match key {
+                    b"jitter" => &mut b.jitter,
+                    b"timeout" => &mut b.timeout,
+                    _ => &mut b.kill_grace,
+                };
+                if slot.is_some() {
+                    return Err(duplicate_key(data, key));
+                }
+                // Zero means no delay, or SIGKILL straight away
+                let parse = match key {
+                    b"timeout" => parse_duration,
+                    _ => parse_duration_or_zero,
+                };
+                let secs = std::str::from_utf8(trim_ascii(value))
+                    .map_err(|_| "invalid UTF-8".to_string())
+                    .and_then(parse)
+                    .map_err(|e| {
+                        let message = format!("invalid {}: {}", as_debug_str(key), e);
+                        invalid_value(data, key, value, message)
+                    })?;
+                *slot = Some(Duration::from_secs(secs));
             }
+            b"exclude" => {
+                if !b.exclude.is_empty() {
+                    return Err(duplicate_key(data, key));
+                }
+                let s = std::str::from_utf8(value)
+                    .map_err(|_| invalid_value(data, key, value, "invalid UTF-8 in exclude"))?;
+                b.exclude = parse_name_list(s).map_err(|e| invalid_value(data, key, value, e))?;
+            }
+            b"max_parallel" => {
+                if b.max_parallel.is_some() {
+                    return Err(duplicate_key(data, key));
+                }
+                let max =
+                    parse_limit(key, value).map_err(|e| invalid_value(data, key, value, e))?;
+                b.max_parallel = Some(max);
+            }
+            b"overlap" => {
+                if b.overlap.is_some() {
+                    return Err(duplicate_key(data, key));
+                }
+                let overlap =
+                    parse_overlap(value).map_err(|e| invalid_value(data, key, value, e))?;
+                b.overlap = Some(overlap);
+            }
+            b"shell" => {
+                if b.shell.is_some() {
+                    return Err(duplicate_key(data, key));
+                }
+                let shell = std::str::from_utf8(trim_ascii(value))
+                    .ok()
+                    .and_then(Shell::from_name)
+                    .ok_or_else(|| {
+                        invalid_value(
+                            data,
+                            key,
+                            value,
+                            "shell must be one of sh, bash, cmd, powershell, none",
+                        )
+                    })?;
+                b.shell = Some(shell);
+            }
+            b"not_before" | b"not_after" => {
+                let slot = match key {
+                    b"not_before" => &mut b.not_before,
+                    _ => &mut b.not_after,
+                };
+                if slot.is_some() {
+                    return Err(duplicate_key(data, key));
+                }
+                let s = std::str::from_utf8(trim_ascii(value)).map_err(|_| {
+                    let message = format!("invalid UTF-8 in {}", as_debug_str(key));
+                    invalid_value(data, key, value, message)
+                })?;
+                *slot = Some(s);
+            }
+            _ => return Err(unknown_key(data, key)),
         }
+        Ok(())
     }
+}
 
-    // finalize last section
-    if let Some(prev) = cur.take() {
-        let start_line = prev.first_line;
-        let job = match finalize_job(&mut cron, prev) {
-            Ok(j) => j,
-            Err(e) => return Err(format!("line {}: {}", start_line, e)),
-        };
-        if !seen_ids.insert(job.id.clone()) {
-            return Err(format!("duplicate job id '{}'", job.id));
-        }
-        jobs.push(job);
+/// Resolves a `timezone` value, loading each distinct zone only once.
+fn parse_timezone<'a>(
+    zones: &mut HashMap<&'a str, Arc<TimeZone>>,
+    value: &'a [u8],
+) -> Result<Arc<TimeZone>, String> {
+    let name = match std::str::from_utf8(trim_ascii(value)) {
+        Ok(s) => s,
+        Err(_) => return Err("invalid UTF-8 in timezone".into()),
+    };
+
+    if let Some(zone) = zones.get(name) {
+        return Ok(zone.clone());
     }
 
-    Ok(jobs)
+    let zone = Arc::new(TimeZone::named(name)?);
+    zones.insert(name, zone.clone());
+    Ok(zone)
+}
+
+fn parse_dst_policy(value: &[u8]) -> Result<DstPolicy, String> {
+    std::str::from_utf8(trim_ascii(value))
+        .ok()
+        .and_then(DstPolicy::from_name)
+        .ok_or_else(|| "dst_policy must be one of skip, shift, twice".to_string())
+}
+
+fn parse_limit(key: &[u8], value: &[u8]) -> Result<usize, String> {
+    std::str::from_utf8(trim_ascii(value))
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .filter(|&n| n > 0)
+        .ok_or_else(|| format!("{} must be a positive integer", as_debug_str(key)))
+}
+
+fn parse_overlap(value: &[u8]) -> Result<Overlap, String> {
+    std::str::from_utf8(trim_ascii(value))
+        .ok()
+        .and_then(Overlap::from_name)
+        .ok_or_else(|| "overlap must be one of allow, skip, queue, replace".to_string())
+}
+
+fn parse_day_match(value: &[u8]) -> Result<DayMatch, String> {
+    std::str::from_utf8(trim_ascii(value))
+        .ok()
+        .and_then(DayMatch::from_name)
+        .ok_or_else(|| "day_match must be `or` or `and`".to_string())
+}
+
+/// Parses either a single bare value or a `[a, b]` list.
+fn parse_name_list(value: &str) -> Result<Vec<String>, String> {
+    let value = value.trim();
+    if value.starts_with('[') && value.ends_with(']') {
+        parse_list_items(&value[1..value.len() - 1])
+    } else if value.is_empty() {
+        Ok(Vec::new())
+    } else {
+        Ok(vec![value.to_string()])
+    }
 }
 
 /// Parses a list like: [1, 2, "some value", "--help"]
@@ -287,45 +693,180 @@ fn parse_list_items(input: &str) -> Result<Vec<String>, String> {
     Ok(items)
 }
 
-fn finalize_job<'a>(cron: &mut CronParser, b: JobBuilder<'a>) -> Result<JobSpec, String> {
-    let id = b.id.trim();
-    if id.is_empty() {
-        return Err("empty job id".into());
-    }
+/// Builds the job, pushing every problem found onto `errors`. Returns
+/// `None` if there were any.
+fn finalize_job<'a>(
+    cron: &mut CronParser,
+    b: JobBuilder<'a>,
+    settings: &Settings,
+    calendars: &HashMap<&str, Arc<Calendar>>,
+    data: &[u8],
+    errors: &mut Vec<ConfigError>,
+) -> Option<JobSpec> {
+    let id = b.id;
+    let line = b.first_line;
+    let errors_before = errors.len();
+    let job_error = |message: &str| ConfigError::Job {
+        line,
+        id: id.to_string(),
+        message: message.to_string(),
+    };
+    // A key whose value was rejected already has its own error
+    let missing = |key: &'static str| {
+        (!b.rejected.contains(key.as_bytes())).then(|| ConfigError::MissingKey {
+            line,
+            id: id.to_string(),
+            key,
+        })
+    };
 
-    let schedule_str = match b.schedule {
-        Some(s) => s,
-        None => return Err(format!("job '{}': missing schedule", id)),
+    // `H` fields resolve from the job id, so they survive restarts
+    cron.set_seed(hash_id(id));
+    let mut schedule = match b.schedule {
+        Some(s) => match cron.parse_schedule(s) {
+            Ok(schedule) => Some(schedule),
+            Err(error) => {
+                let (line, col) = locate(data, s.as_bytes());
+                errors.push(ConfigError::InvalidSchedule {
+                    line,
+                    col,
+                    id: id.to_string(),
+                    error,
+                });
+                None
+            }
+        },
+        None => {
+            errors.extend(missing("schedule"));
+            None
+        }
     };
 
-    let schedule = match cron.parse(schedule_str) {
-        Ok(s) => s,
-        Err(e) => return Err(format!("job '{}': invalid schedule: {}", id, e)),
+    let timezone = b
+        .timezone
+        .or_else(|| settings.timezone.clone())
+        .unwrap_or_else(|| Arc::new(TimeZone::local()));
+
+    let mut exclude = Vec::with_capacity(b.exclude.len());
+    for name in &b.exclude {
+        match calendars.get(name.as_str()) {
+            Some(c) => exclude.push(c.clone()),
+            None => errors.push(job_error(&format!("unknown calendar '{}'", name))),
+        }
+    }
+
+    match &mut schedule {
+        Some(Schedule::Cron(cron)) => {
+            cron.day_match = b.day_match.or(settings.day_match).unwrap_or_default();
+            cron.exclude = exclude;
+        }
+        Some(Schedule::Interval(interval)) => {
+            interval.exclude = exclude;
+            if let Some(anchor) = b.anchor {
+                match DateTime::parse(anchor) {
+                    Ok(local) => interval.anchor = Some(timezone.to_utc(&local)),
+                    Err(e) => {
+                        let message = format!("job '{}': invalid anchor: {}", id, e);
+                        errors.push(invalid_value(data, b"anchor", anchor.as_bytes(), message));
+                    }
+                }
+            }
+        }
+        Some(Schedule::At(_) | Schedule::Reboot) if !b.exclude.is_empty() => {
+            errors.push(job_error(
+                "`exclude` only applies to cron and `@every` schedules",
+            ));
+        }
+        _ => {}
+    }
+
+    // Window bounds are wall-clock times in the job's zone
+    let bound = |name: &str, value: Option<&str>| -> Result<Option<SystemTime>, ConfigError> {
+        let Some(value) = value else {
+            return Ok(None);
+        };
+        let local = DateTime::parse(value).map_err(|e| {
+            let message = format!("job '{}': invalid {}: {}", id, name, e);
+            invalid_value(data, name.as_bytes(), value.as_bytes(), message)
+        })?;
+        Ok(Some(unix_to_system_time(timezone.to_utc(&local))))
+    };
+    let mut bound_or_report = |name: &str, value: Option<&str>| {
+        bound(name, value).unwrap_or_else(|e| {
+            errors.push(e);
+            None
+        })
     };
+    let not_before = bound_or_report("not_before", b.not_before);
+    let not_after = bound_or_report("not_after", b.not_after);
+    if let (Some(nb), Some(na)) = (not_before, not_after) {
+        if nb > na {
+            errors.push(job_error("`not_before` is later than `not_after`"));
+        }
+    }
+
+    let interval = matches!(schedule, None | Some(Schedule::Interval(_)));
+    if b.anchor.is_some() && !interval {
+        errors.push(job_error("`anchor` only applies to `@every` schedules"));
+    }
 
-    let command_str = match b.command {
-        Some(c) => c,
-        None => return Err(format!("job '{}': missing command", id)),
+    // Pre-parse base command once. A shell gets the command line verbatim.
+    let shell = b.shell.unwrap_or_default();
+    let base_cmd = match (b.command, shell) {
+        (Some(command), Shell::None) => match try_get_command_as_os_str(command) {
+            Ok(argv) => Some(argv),
+            Err(e) => {
+                let message = format!("job '{}': invalid command: {}", id, e);
+                errors.push(invalid_value(data, b"command", command.as_bytes(), message));
+                None
+            }
+        },
+        (Some(command), _) => Some(vec![OsString::from(command)]),
+        (None, _) => {
+            errors.extend(missing("command"));
+            None
+        }
     };
 
-    // Pre-parse base command once
-    let base_cmd = get_command_as_os_str(command_str);
+    let (Some(schedule), Some(base_cmd)) = (schedule, base_cmd) else {
+        return None;
+    };
+    if errors.len() > errors_before {
+        return None;
+    }
 
     // Build fanout plan
     let fanout = if let Some(n) = b.fanout_int {
         Fanout::Int(n)
     } else if !b.fanout_list.is_empty() {
-        // Prepare full argv per fanout entry: base_cmd + parsed extras
-        let extras: Vec<String> = b.fanout_list.into_iter().map(|s| s.to_string()).collect();
-        let list = crate::models::JobSpec::build_fanout_list_from_strings(&base_cmd, &extras);
+        let list = match shell {
+            // Prepare full argv per fanout entry: base_cmd + parsed extras
+            Shell::None => JobSpec::build_fanout_list_from_strings(&base_cmd, &b.fanout_list),
+            // Extras are appended to the command line for the shell to split
+            _ => {
+                let line = base_cmd[0].to_string_lossy();
+                let cmds = b.fanout_list.iter().map(|e| format!("{} {}", line, e));
+                cmds.map(|c| vec![OsString::from(c)]).collect()
+            }
+        };
         Fanout::List(list)
     } else {
         Fanout::None
     };
 
-    Ok(JobSpec {
+    Some(JobSpec {
         id: id.to_string(),
         schedule,
+        timezone,
+        dst_policy: b.dst_policy.or(settings.dst_policy).unwrap_or_default(),
+        jitter: b.jitter.unwrap_or_default().min(MAX_JITTER),
+        timeout: b.timeout,
+        kill_grace: b.kill_grace.unwrap_or(DEFAULT_KILL_GRACE),
+        overlap: b.overlap.unwrap_or_default(),
+        max_parallel: b.max_parallel,
+        not_before,
+        not_after,
+        shell,
         base_cmd,
         fanout,
     })
@@ -354,10 +895,10 @@ fn is_ascii_ws(b: u8) -> bool {
 }
 
 #[inline]
-fn parse_section_header(line: &[u8]) -> Option<&str> {
-    // Accept exactly: [job:<id>]
-    if line.len() >= 7 && line.starts_with(b"[job:") && line.ends_with(b"]") {
-        let inner = &line[5..line.len() - 1];
+fn parse_section_header<'a>(line: &'a [u8], prefix: &[u8]) -> Option<&'a str> {
+    // Accept exactly: <prefix><id>], e.g. [job:<id>]
+    if line.len() > prefix.len() + 1 && line.starts_with(prefix) && line.ends_with(b"]") {
+        let inner = &line[prefix.len()..line.len() - 1];
         let id_bytes = trim_ascii(inner);
         if id_bytes.is_empty() {
             return None;
@@ -377,6 +918,13 @@ fn memchr(hay: &[u8], needle: u8) -> Option<usize> {
     None
 }
 
+/// A `#` opens a comment at the start of a line or after whitespace, so
+/// `MON#2` in a schedule is left alone.
+#[inline]
+fn comment_start(line: &[u8]) -> Option<usize> {
+    (0..line.len()).find(|&i| line[i] == b'#' && (i == 0 || is_ascii_ws(line[i - 1])))
+}
+
 #[inline]
 fn parse_key_value(line: &[u8]) -> Option<(&[u8], &[u8])> {
     let eq = memchr(line, b'=')?;
@@ -385,6 +933,52 @@ fn parse_key_value(line: &[u8]) -> Option<(&[u8], &[u8])> {
     Some((key, val))
 }
 
+/// 1-based line and character column of `part`, a slice of `data`.
+fn locate(data: &[u8], part: &[u8]) -> (usize, usize) {
+    let before = &data[..part.as_ptr() as usize - data.as_ptr() as usize];
+    let line_start = before
+        .iter()
+        .rposition(|&b| b == b'\n')
+        .map_or(0, |i| i + 1);
+    let line = before.iter().filter(|&&b| b == b'\n').count() + 1;
+    (line, char_count(&before[line_start..]) + 1)
+}
+
+#[inline]
+fn char_count(bytes: &[u8]) -> usize {
+    bytes.iter().filter(|&&b| b & 0xC0 != 0x80).count()
+}
+
+fn duplicate_key(data: &[u8], key: &[u8]) -> ConfigError {
+    let (line, col) = locate(data, key);
+    ConfigError::DuplicateKey {
+        line,
+        col,
+        key: as_debug_str(key),
+    }
+}
+
+fn unknown_key(data: &[u8], key: &[u8]) -> ConfigError {
+    let (line, col) = locate(data, key);
+    ConfigError::UnknownKey {
+        line,
+        col,
+        key: as_debug_str(key),
+    }
+}
+
+/// `value`, a slice of `data`, was rejected for `key`.
+fn invalid_value(data: &[u8], key: &[u8], value: &[u8], message: impl Into<String>) -> ConfigError {
+    let (line, col) = locate(data, value);
+    ConfigError::InvalidValue {
+        line,
+        col,
+        len: char_count(value),
+        key: as_debug_str(key),
+        message: message.into(),
+    }
+}
+
 #[inline]
 fn as_debug_str(key: &[u8]) -> String {
     match std::str::from_utf8(key) {
@@ -1,14 +1,25 @@
-use std::{env, fs, io, path::PathBuf};
+use std::path::{Path, PathBuf};
+use std::{env, fs, io};
 
-use croner::loader::ConfigCache;
+use croner::error::ConfigErrors;
+use croner::loader::{load_config, ConfigCache};
+use croner::parser::CronParser;
 use croner::printer::Printer;
 use croner::scheduler::Scheduler;
+use croner::state::FiredLog;
+use croner::supervisor;
 
 fn main() {
     let mut config_path = PathBuf::from("config.croner");
     let mut print_enabled = true;
 
-    for arg in env::args().skip(1) {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("describe") {
+        describe(&args[1..]);
+        return;
+    }
+
+    for arg in args {
         match arg.as_str() {
             "--version" | "-v" => {
                 println!("croner {}", env!("CARGO_PKG_VERSION"));
@@ -34,23 +45,53 @@ fn main() {
 
     let mut cache = ConfigCache::new();
     if let Err(e) = cache.reload_if_changed(&config_path) {
-        eprintln!("Failed to load config: {}", e);
+        report_config_errors(&config_path, &e);
         std::process::exit(1);
     }
 
     let printer = Printer::new(print_enabled);
     let mut scheduler = Scheduler::new(cache, printer);
 
+    let state_path = FiredLog::path_for(&config_path);
+    match FiredLog::open(&state_path) {
+        Ok(fired) => {
+            for line in fired.skipped_lines() {
+                eprintln!(
+                    "Ignoring malformed entry on line {} of {}",
+                    line,
+                    state_path.display()
+                );
+            }
+            scheduler.set_fired_log(fired);
+        }
+        Err(e) => {
+            eprintln!("Failed to load state: {}", e);
+            std::process::exit(1);
+        }
+    }
+
+    supervisor::catch_shutdown_signals();
     scheduler.init();
     scheduler.run(&config_path);
 }
 
+const BANNER: &str = r#"
+   ______                          
+  / ____/________  ____  ___  _____
+ / /   / ___/ __ \/ __ \/ _ \/ ___/
+/ /___/ /  / /_/ / / / /  __/ /    
+\____/_/   \____/_/ /_/\___/_/     
+                                   
+                                   
+"#;
+
 fn print_help() {
     println!(
         "\x1b[1;36m{}\x1b[0m - A high-performance cron-style job runner
 
 \x1b[1mUSAGE:\x1b[0m
     \x1b[32mcroner\x1b[0m [OPTIONS]
+    \x1b[32mcroner\x1b[0m describe [--config=<path>] [<schedule>]
 
 \x1b[1mOPTIONS:\x1b[0m
     \x1b[33m--config=<path>\x1b[0m    Path to config file (default: ./config.croner)
@@ -65,19 +106,62 @@ fn print_help() {
     croner --print=false
     croner --version
     croner --uninstall
+    croner describe \"*/15 9-17 * * 1-5\"
+    croner describe --config=/etc/croner/jobs.croner
 ",
-        r#"
-   ______                          
-  / ____/________  ____  ___  _____
- / /   / ___/ __ \/ __ \/ _ \/ ___/
-/ /___/ /  / /_/ / / / /  __/ /    
-\____/_/   \____/_/ /_/\___/_/     
-                                   
-                                   
-"#
+        BANNER
     );
 }
 
+/// `croner describe`: explains one schedule, or every job in the config.
+fn describe(args: &[String]) {
+    let mut config_path = PathBuf::from("config.croner");
+    let mut expr = Vec::new();
+    for arg in args {
+        match arg.strip_prefix("--config=") {
+            Some(path) => config_path = PathBuf::from(path),
+            None => expr.push(arg.as_str()),
+        }
+    }
+
+    if !expr.is_empty() {
+        let expr = expr.join(" ");
+        match CronParser::new().parse_schedule(&expr) {
+            Ok(schedule) => println!("{}", schedule.describe()),
+            Err(e) => {
+                eprintln!("Invalid schedule: {}", e.render(&expr));
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    match load_config(&config_path) {
+        Ok(jobs) => {
+            for job in jobs {
+                println!("{}: {}", job.id, job.schedule.describe());
+            }
+        }
+        Err(e) => {
+            report_config_errors(&config_path, &e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Prints every config error, underlining the offending part when known.
+fn report_config_errors(path: &Path, errors: &ConfigErrors) {
+    let source = fs::read_to_string(path).unwrap_or_default();
+    match errors.errors().len() {
+        1 => eprintln!("Failed to load config: {}", errors.render(&source)),
+        n => eprintln!(
+            "Failed to load config: {} errors\n\n{}",
+            n,
+            errors.render(&source)
+        ),
+    }
+}
+
 #[inline]
 fn home_dir() -> Option<PathBuf> {
     if let Ok(home) = env::var("HOME") {
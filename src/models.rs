@@ -1,7 +1,42 @@
 use std::ffi::OsString;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
 
-use crate::parser::CronSchedule;
-use crate::shell::get_command_as_os_str;
+use crate::parser::{DstPolicy, Schedule};
+use crate::shell::{get_command_as_os_str, Shell};
+use crate::tz::TimeZone;
+
+/// How long a run gets to exit after SIGTERM when `kill_grace` is not set.
+pub const DEFAULT_KILL_GRACE: Duration = Duration::from_secs(30);
+
+/// Longest `jitter` window; longer ones are capped to it.
+pub const MAX_JITTER: Duration = Duration::from_secs(7 * 86_400);
+
+/// What happens when a run is due while the previous one is still going.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Overlap {
+    /// Start the new run alongside the old one.
+    #[default]
+    Allow,
+    /// Drop the new run.
+    Skip,
+    /// Start the new run once the old one ends. At most one run waits.
+    Queue,
+    /// Stop the old run and start the new one.
+    Replace,
+}
+
+impl Overlap {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "allow" => Some(Self::Allow),
+            "skip" => Some(Self::Skip),
+            "queue" => Some(Self::Queue),
+            "replace" => Some(Self::Replace),
+            _ => None,
+        }
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct JobInstance {
@@ -23,9 +58,38 @@ pub enum Fanout {
 #[derive(Debug, Clone)]
 pub struct JobSpec {
     pub id: String,
-    pub schedule: CronSchedule,
+    pub schedule: Schedule,
+
+    /// Zone the schedule's wall-clock fields are evaluated in.
+    pub timezone: Arc<TimeZone>,
+
+    /// Handling of runs that fall into a DST gap or overlap.
+    pub dst_policy: DstPolicy,
+
+    /// Upper bound of the random delay added to each scheduled run.
+    pub jitter: Duration,
+
+    /// Longest a run may take before it is stopped.
+    pub timeout: Option<Duration>,
+
+    /// Time between SIGTERM and SIGKILL when a run is stopped.
+    pub kill_grace: Duration,
+
+    /// Handling of runs that are due while an earlier one is in flight.
+    pub overlap: Overlap,
+
+    /// Most instances of the job running at once, fanout included.
+    pub max_parallel: Option<usize>,
+
+    /// Window outside of which the job never runs.
+    pub not_before: Option<SystemTime>,
+    pub not_after: Option<SystemTime>,
+
+    /// Interpreter the command runs under, if any.
+    pub shell: Shell,
 
-    /// Pre-parsed base command tokens
+    /// Pre-parsed base command tokens. With a shell, the command line as a
+    /// single element.
     pub base_cmd: Vec<OsString>,
 
     /// Prepared fanout plan.
@@ -1,6 +1,32 @@
-#[derive(Debug, Clone)]
+use std::fmt;
+use std::ops::Range;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use crate::calendar::Calendar;
+use crate::datetime::{
+    civil_from_days, days_from_civil, days_in_month, system_time_to_unix, unix_to_system_time,
+    DateTime,
+};
+use crate::error::{CronError, Field};
+use crate::tz::TimeZone;
+
+/// Upper bound on how far ahead `next_match` searches before giving up on a
+/// schedule that can never fire (e.g. `0 0 30 2 *`).
+const MAX_SEARCH_YEARS: i32 = 400;
+
+/// Bounds of the optional seventh (year) field.
+pub const MIN_YEAR: i32 = 1970;
+pub const MAX_YEAR: i32 = 2099;
+
+/// Longest `@every` interval, in seconds: a hundred years.
+pub const MAX_INTERVAL: u64 = 36_525 * 86_400;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct CronSchedule {
     // Bitmasks (original behavior preserved)
+    pub second: u64, // bits 0..59 (only bit 0 for 5-field expressions)
     pub minute: u64, // bits 0..59
     pub hour: u32,   // bits 0..23
     pub day: u32,    // bits 1..31 (bit 0 unused)
@@ -8,74 +34,1031 @@ pub struct CronSchedule {
     pub weekday: u8, // bits 0..6
 
     // Precomputed sorted allowed values for fast "next" computations
-    pub minutes: Vec<u8>,  // 0..59
-    pub hours: Vec<u8>,    // 0..23
-    pub days: Vec<u8>,     // 1..31
-    pub months: Vec<u8>,   // 1..12
-    pub weekdays: Vec<u8>, // 0..6
+    pub seconds: Vec<u8>,        // 0..59
+    pub minutes: Vec<u8>,        // 0..59
+    pub hours: Vec<u8>,          // 0..23
+    pub days: Vec<u8>,           // 1..31
+    pub months: Vec<u8>,         // 1..12
+    pub weekdays: Vec<u8>,       // 0..6
+    pub years: Option<Vec<i32>>, // MIN_YEAR..MAX_YEAR; `None` is any year
+
+    // Calendar-relative rules (`L`, `W`, `#`) that cannot be plain bitmasks
+    pub day_rules: Vec<DayRule>,
+    pub weekday_rules: Vec<WeekdayRule>,
+
+    // Whether day-of-month / day-of-week were anything other than `*` or `?`
+    pub day_restricted: bool,
+    pub weekday_restricted: bool,
+
+    /// How day-of-month and day-of-week combine when both are restricted.
+    pub day_match: DayMatch,
+
+    /// Days on which the schedule never fires.
+    pub exclude: Vec<Arc<Calendar>>,
+}
+
+/// Any schedule a job can have.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[allow(clippy::large_enum_variant)] // one per job, never in hot collections
+pub enum Schedule {
+    Cron(CronSchedule),
+    /// `@every <duration>`: a fixed interval.
+    Interval(IntervalSchedule),
+    /// `@at <date time>`: once, at a wall-clock time in the job's zone.
+    At(DateTime),
+    /// `@reboot`: once when croner starts.
+    Reboot,
+}
+
+/// Runs every `every` seconds on a grid through `anchor`, or through the
+/// scheduler's start time when no anchor is configured.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IntervalSchedule {
+    pub every: u64,
+    pub anchor: Option<i64>,
+    /// Days (in the job's zone) on which ticks are dropped.
+    pub exclude: Vec<Arc<Calendar>>,
+}
+
+impl IntervalSchedule {
+    /// First tick strictly after `after`. `start` stands in for a missing
+    /// anchor. `None` if the tick is too far out to represent.
+    pub fn next_after(&self, after: i64, start: i64) -> Option<i64> {
+        let anchor = self.anchor.unwrap_or(start);
+        let every = i64::try_from(self.every).ok().filter(|&e| e > 0)?;
+        let ticks = after
+            .checked_sub(anchor)?
+            .div_euclid(every)
+            .checked_add(1)?;
+        anchor.checked_add(ticks.checked_mul(every)?)
+    }
+
+    /// Like `next_after`, but skips ticks on excluded days of `tz`.
+    pub fn next_after_in(&self, after: i64, start: i64, tz: &TimeZone) -> Option<i64> {
+        let mut when = self.next_after(after, start)?;
+        loop {
+            let local = tz.to_local(when);
+            let day = days_from_civil(local.year, local.month, local.day);
+            let open = next_open_day(&self.exclude, day);
+            if open == day {
+                return Some(when);
+            }
+            let (y, m, d) = civil_from_days(open);
+            let midnight = tz.to_utc(&DateTime::new(y, m, d, 0, 0, 0));
+            when = self.next_after(midnight - 1, start)?;
+        }
+    }
+}
+
+/// First day at or after `day` that none of the calendars exclude.
+fn next_open_day(calendars: &[Arc<Calendar>], mut day: i64) -> i64 {
+    loop {
+        let open = calendars
+            .iter()
+            .map(|c| c.next_open_day(day))
+            .max()
+            .unwrap_or(day);
+        if open == day {
+            return day;
+        }
+        day = open;
+    }
+}
+
+/// Day-of-month rules relative to the month's length or working days.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DayRule {
+    /// `L` / `L-n`: the last day of the month, or `n` days before it.
+    Last(u8),
+    /// `LW`: the last Monday-to-Friday day of the month.
+    LastWeekday,
+    /// `nW`: the Monday-to-Friday day nearest to day `n`, within the month.
+    NearestWeekday(u8),
+}
+
+/// Day-of-week rules relative to the month.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WeekdayRule {
+    /// `dL`: the last weekday `d` of the month (`5L` = last Friday).
+    Last(u8),
+    /// `d#n`: the `n`-th weekday `d` of the month (`2#2` = second Tuesday).
+    Nth(u8, u8),
+}
+
+impl DayRule {
+    pub fn matches(&self, year: i32, month: u8, day: u8) -> bool {
+        let dim = days_in_month(year, month);
+        match *self {
+            DayRule::Last(offset) => offset < dim && day == dim - offset,
+            DayRule::LastWeekday => {
+                let last = match weekday_of(year, month, dim) {
+                    6 => dim - 1,
+                    0 => dim - 2,
+                    _ => dim,
+                };
+                day == last
+            }
+            DayRule::NearestWeekday(target) => {
+                if target > dim {
+                    return false;
+                }
+                let nearest = match weekday_of(year, month, target) {
+                    // Saturday: Friday before, unless that leaves the month
+                    6 if target == 1 => 3,
+                    6 => target - 1,
+                    // Sunday: Monday after, unless that leaves the month
+                    0 if target == dim => target - 2,
+                    0 => target + 1,
+                    _ => target,
+                };
+                day == nearest
+            }
+        }
+    }
+}
+
+impl WeekdayRule {
+    pub fn matches(&self, year: i32, month: u8, day: u8) -> bool {
+        let weekday = weekday_of(year, month, day);
+        match *self {
+            WeekdayRule::Last(d) => weekday == d && day + 7 > days_in_month(year, month),
+            WeekdayRule::Nth(d, n) => weekday == d && (day - 1) / 7 + 1 == n,
+        }
+    }
+}
+
+#[inline]
+fn weekday_of(year: i32, month: u8, day: u8) -> u8 {
+    DateTime::new(year, month, day, 0, 0, 0).weekday()
+}
+
+/// Combination rule for the day-of-month and day-of-week fields.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DayMatch {
+    /// Classic (Vixie) cron: if both fields are restricted, either may match.
+    #[default]
+    Or,
+    /// Both fields must match.
+    And,
+}
+
+impl DayMatch {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "or" => Some(Self::Or),
+            "and" => Some(Self::And),
+            _ => None,
+        }
+    }
+}
+
+impl CronSchedule {
+    /// True if every field of the schedule accepts the given wall-clock second.
+    pub fn matches(&self, dt: &DateTime) -> bool {
+        (self.second >> dt.second) & 1 == 1
+            && (self.minute >> dt.minute) & 1 == 1
+            && (self.hour >> dt.hour) & 1 == 1
+            && (self.month >> dt.month) & 1 == 1
+            && self.years.as_ref().map_or(true, |y| y.contains(&dt.year))
+            && self.day_matches(dt.year, dt.month, dt.day)
+    }
+
+    #[inline]
+    fn day_matches(&self, year: i32, month: u8, day: u8) -> bool {
+        if self.exclude.iter().any(|c| c.contains(year, month, day)) {
+            return false;
+        }
+
+        let weekday = weekday_of(year, month, day);
+        let dom = (self.day >> day) & 1 == 1
+            || self.day_rules.iter().any(|r| r.matches(year, month, day));
+        let dow = (self.weekday >> weekday) & 1 == 1
+            || self
+                .weekday_rules
+                .iter()
+                .any(|r| r.matches(year, month, day));
+
+        if self.days_either() {
+            dom || dow
+        } else {
+            dom && dow
+        }
+    }
+
+    /// True when a day qualifies by matching either day field.
+    #[inline]
+    pub(crate) fn days_either(&self) -> bool {
+        self.day_match == DayMatch::Or && self.day_restricted && self.weekday_restricted
+    }
+
+    /// Finds the first wall-clock second strictly after `after` that matches
+    /// every field. Walks field-by-field (month, day, hour, minute, second)
+    /// using the precomputed sorted lists, so a yearly schedule costs a
+    /// handful of steps rather than half a million minute checks.
+    ///
+    /// Returns `None` if nothing matches within `MAX_SEARCH_YEARS`.
+    pub fn next_match(&self, after: &DateTime) -> Option<DateTime> {
+        if self.seconds.is_empty()
+            || self.minutes.is_empty()
+            || self.hours.is_empty()
+            || (self.days.is_empty() && self.day_rules.is_empty())
+            || self.months.is_empty()
+            || (self.weekdays.is_empty() && self.weekday_rules.is_empty())
+        {
+            return None;
+        }
+
+        let start = DateTime::from_unix(after.to_unix() + 1);
+        let limit = start.year.saturating_add(MAX_SEARCH_YEARS);
+
+        let mut year = start.year;
+        let mut month = start.month;
+        let mut day = start.day;
+        let mut hour = start.hour;
+        let mut minute = start.minute;
+        let mut second = start.second;
+
+        loop {
+            if year > limit {
+                return None;
+            }
+
+            if let Some(years) = &self.years {
+                match years.iter().copied().find(|&y| y >= year) {
+                    Some(y) if y == year => {}
+                    Some(y) => {
+                        year = y;
+                        (month, day, hour, minute, second) = (1, 1, 0, 0, 0);
+                    }
+                    None => return None,
+                }
+            }
+
+            match first_at_or_after(&self.months, month) {
+                Some(m) if m == month => {}
+                Some(m) => {
+                    month = m;
+                    (day, hour, minute, second) = (1, 0, 0, 0);
+                }
+                None => {
+                    year += 1;
+                    (month, day, hour, minute, second) = (1, 1, 0, 0, 0);
+                    continue;
+                }
+            }
+
+            let dim = days_in_month(year, month);
+            let scan_all =
+                self.days_either() || !self.day_rules.is_empty() || !self.weekday_rules.is_empty();
+            let next_day = if scan_all {
+                // A day outside `days` can still qualify through a rule or its weekday.
+                (day..=dim).find(|&d| self.day_matches(year, month, d))
+            } else {
+                self.days
+                    .iter()
+                    .copied()
+                    .filter(|&d| d >= day && d <= dim)
+                    .find(|&d| self.day_matches(year, month, d))
+            };
+
+            match next_day {
+                Some(d) if d == day => {}
+                Some(d) => {
+                    day = d;
+                    (hour, minute, second) = (0, 0, 0);
+                }
+                None => {
+                    (year, month) = next_month(year, month);
+                    (day, hour, minute, second) = (1, 0, 0, 0);
+                    continue;
+                }
+            }
+
+            match first_at_or_after(&self.hours, hour) {
+                Some(h) if h == hour => {}
+                Some(h) => {
+                    hour = h;
+                    (minute, second) = (0, 0);
+                }
+                None => {
+                    (year, month, day) = next_day_of(year, month, day);
+                    (hour, minute, second) = (0, 0, 0);
+                    continue;
+                }
+            }
+
+            match first_at_or_after(&self.minutes, minute) {
+                Some(m) if m == minute => {}
+                Some(m) => {
+                    minute = m;
+                    second = 0;
+                }
+                None => {
+                    (minute, second) = (0, 0);
+                    (year, month, day, hour) = next_hour_of(year, month, day, hour);
+                    continue;
+                }
+            }
+
+            match first_at_or_after(&self.seconds, second) {
+                Some(s) => return Some(DateTime::new(year, month, day, hour, minute, s)),
+                None => {
+                    second = 0;
+                    if minute == 59 {
+                        minute = 0;
+                        (year, month, day, hour) = next_hour_of(year, month, day, hour);
+                    } else {
+                        minute += 1;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Finds the last wall-clock second strictly before `before` that
+    /// matches every field, walking the fields backwards the way
+    /// `next_match` walks them forwards.
+    ///
+    /// Returns `None` if nothing matches within `MAX_SEARCH_YEARS`.
+    pub fn prev_match(&self, before: &DateTime) -> Option<DateTime> {
+        if self.seconds.is_empty()
+            || self.minutes.is_empty()
+            || self.hours.is_empty()
+            || (self.days.is_empty() && self.day_rules.is_empty())
+            || self.months.is_empty()
+            || (self.weekdays.is_empty() && self.weekday_rules.is_empty())
+        {
+            return None;
+        }
+
+        let start = DateTime::from_unix(before.to_unix() - 1);
+        let limit = start.year.saturating_sub(MAX_SEARCH_YEARS);
+
+        let mut year = start.year;
+        let mut month = start.month;
+        let mut day = start.day;
+        let mut hour = start.hour;
+        let mut minute = start.minute;
+        let mut second = start.second;
+
+        // A day of 31 stands for the last day of whatever month it lands in
+        loop {
+            if year < limit {
+                return None;
+            }
+
+            if let Some(years) = &self.years {
+                match years.iter().copied().rev().find(|&y| y <= year) {
+                    Some(y) if y == year => {}
+                    Some(y) => {
+                        year = y;
+                        (month, day, hour, minute, second) = (12, 31, 23, 59, 59);
+                    }
+                    None => return None,
+                }
+            }
+
+            match last_at_or_before(&self.months, month) {
+                Some(m) if m == month => {}
+                Some(m) => {
+                    month = m;
+                    (day, hour, minute, second) = (31, 23, 59, 59);
+                }
+                None => {
+                    year -= 1;
+                    (month, day, hour, minute, second) = (12, 31, 23, 59, 59);
+                    continue;
+                }
+            }
+
+            day = day.min(days_in_month(year, month));
+            let scan_all =
+                self.days_either() || !self.day_rules.is_empty() || !self.weekday_rules.is_empty();
+            let prev_day = if scan_all {
+                (1..=day).rev().find(|&d| self.day_matches(year, month, d))
+            } else {
+                self.days
+                    .iter()
+                    .rev()
+                    .copied()
+                    .filter(|&d| d <= day)
+                    .find(|&d| self.day_matches(year, month, d))
+            };
+
+            match prev_day {
+                Some(d) if d == day => {}
+                Some(d) => {
+                    day = d;
+                    (hour, minute, second) = (23, 59, 59);
+                }
+                None => {
+                    (year, month) = prev_month(year, month);
+                    (day, hour, minute, second) = (31, 23, 59, 59);
+                    continue;
+                }
+            }
+
+            match last_at_or_before(&self.hours, hour) {
+                Some(h) if h == hour => {}
+                Some(h) => {
+                    hour = h;
+                    (minute, second) = (59, 59);
+                }
+                None => {
+                    (year, month, day) = prev_day_of(year, month, day);
+                    (hour, minute, second) = (23, 59, 59);
+                    continue;
+                }
+            }
+
+            match last_at_or_before(&self.minutes, minute) {
+                Some(m) if m == minute => {}
+                Some(m) => {
+                    minute = m;
+                    second = 59;
+                }
+                None => {
+                    (minute, second) = (59, 59);
+                    (year, month, day, hour) = prev_hour_of(year, month, day, hour);
+                    continue;
+                }
+            }
+
+            match last_at_or_before(&self.seconds, second) {
+                Some(s) => return Some(DateTime::new(year, month, day, hour, minute, s)),
+                None => {
+                    second = 59;
+                    if minute == 0 {
+                        minute = 59;
+                        (year, month, day, hour) = prev_hour_of(year, month, day, hour);
+                    } else {
+                        minute -= 1;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Next UTC instant (Unix seconds) strictly after `after` at which the
+    /// schedule fires when its fields are read as wall-clock time in `tz`.
+    ///
+    /// The search walks the zone's constant-offset segments; `policy` decides
+    /// what happens to wall-clock times a DST shift skips or repeats. As in
+    /// Vixie cron, a schedule that fires every hour runs on elapsed time and
+    /// so goes through both passes of a repeated hour whatever the policy.
+    pub fn next_after_in(&self, after: i64, tz: &TimeZone, policy: DstPolicy) -> Option<i64> {
+        let repeat = policy == DstPolicy::Twice || self.hours.len() == 24;
+        let mut segment = after;
+        let mut local_after = after + tz.offset_at(after) as i64;
+
+        // Already inside a repeated hour: its first pass has been served.
+        if !repeat {
+            if let Some(prev) = tz.prev_transition(after) {
+                let before = tz.offset_at(prev - 1) as i64;
+                local_after = local_after.max(prev + before - 1);
+            }
+        }
+
+        loop {
+            let offset = tz.offset_at(segment) as i64;
+            let cand = self.next_match(&DateTime::from_unix(local_after))?;
+            let cand_utc = cand.to_unix() - offset;
+
+            let end = match tz.next_transition(segment) {
+                Some(end) if cand_utc >= end => end,
+                _ => return Some(cand_utc),
+            };
+
+            let next_offset = tz.offset_at(end) as i64;
+            let in_gap = next_offset > offset && cand.to_unix() < end + next_offset;
+            if in_gap && policy != DstPolicy::Skip {
+                return Some(end);
+            }
+
+            segment = end;
+            local_after = if repeat {
+                end + next_offset - 1
+            } else {
+                (end + next_offset - 1).max(end + offset - 1)
+            };
+        }
+    }
+
+    /// Last UTC instant strictly before `before` at which the schedule fires,
+    /// the mirror image of `next_after_in` under the same `policy`.
+    pub fn prev_before_in(&self, before: i64, tz: &TimeZone, policy: DstPolicy) -> Option<i64> {
+        let repeat = policy == DstPolicy::Twice || self.hours.len() == 24;
+        // Walks the zone's constant-offset segments backwards, each ending
+        // just before `end`
+        let mut end = before;
+        loop {
+            let offset = tz.offset_at(end - 1) as i64;
+            let cand = self
+                .prev_match(&DateTime::from_unix(end + offset))?
+                .to_unix();
+            let Some(start) = tz.prev_transition(end - 1) else {
+                return Some(cand - offset);
+            };
+
+            // Wall-clock times the previous segment already served don't
+            // fire again, unless repeated hours run twice
+            let prev_offset = tz.offset_at(start - 1) as i64;
+            let first = if !repeat && prev_offset > offset {
+                start + prev_offset
+            } else {
+                start + offset
+            };
+            if cand >= first {
+                return Some(cand - offset);
+            }
+
+            // A run the spring-forward skipped fires at the shift
+            let in_gap = prev_offset < offset && cand >= start + prev_offset;
+            if in_gap && policy != DstPolicy::Skip {
+                return Some(start);
+            }
+            end = start;
+        }
+    }
+
+    /// Next run strictly after `t`, reading the fields as UTC.
+    pub fn next_after(&self, t: SystemTime) -> Option<SystemTime> {
+        self.next_after_in(system_time_to_unix(t), &TimeZone::utc(), DstPolicy::Shift)
+            .map(unix_to_system_time)
+    }
+
+    /// Last run strictly before `t`, reading the fields as UTC.
+    pub fn prev_before(&self, t: SystemTime) -> Option<SystemTime> {
+        // Round up, so a fractional `t` keeps its own whole second
+        let secs = system_time_to_unix(t + Duration::from_nanos(999_999_999));
+        self.prev_before_in(secs, &TimeZone::utc(), DstPolicy::Shift)
+            .map(unix_to_system_time)
+    }
+
+    /// Upcoming runs strictly after `t`, reading the fields as UTC.
+    pub fn iter_after(&self, t: SystemTime) -> Upcoming<'_> {
+        self.iter_after_in(t, TimeZone::utc(), DstPolicy::Shift)
+    }
+
+    /// Upcoming runs strictly after `t`, reading the fields in `tz`.
+    pub fn iter_after_in(&self, t: SystemTime, tz: TimeZone, policy: DstPolicy) -> Upcoming<'_> {
+        Upcoming {
+            schedule: self,
+            tz,
+            policy,
+            cursor: Some(system_time_to_unix(t)),
+        }
+    }
+}
+
+/// Iterator over a schedule's runs, from `CronSchedule::iter_after`.
+#[derive(Debug, Clone)]
+pub struct Upcoming<'a> {
+    schedule: &'a CronSchedule,
+    tz: TimeZone,
+    policy: DstPolicy,
+    cursor: Option<i64>,
+}
+
+impl Iterator for Upcoming<'_> {
+    type Item = SystemTime;
+
+    fn next(&mut self) -> Option<SystemTime> {
+        let next = self
+            .schedule
+            .next_after_in(self.cursor?, &self.tz, self.policy);
+        self.cursor = next;
+        next.map(unix_to_system_time)
+    }
+}
+
+/// What to do with runs whose wall-clock time a DST transition skips
+/// (spring forward) or repeats (fall back).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DstPolicy {
+    /// Runs in a skipped hour are dropped; repeated hours run once unless
+    /// the schedule fires every hour.
+    Skip,
+    /// Runs in a skipped hour fire once at the shift; repeated hours run once
+    /// unless the schedule fires every hour.
+    #[default]
+    Shift,
+    /// Runs in a skipped hour fire once at the shift; repeated hours run on
+    /// both passes.
+    Twice,
+}
+
+impl DstPolicy {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "skip" => Some(Self::Skip),
+            "shift" => Some(Self::Shift),
+            "twice" => Some(Self::Twice),
+            _ => None,
+        }
+    }
+}
+
+#[inline]
+fn first_at_or_after(sorted: &[u8], v: u8) -> Option<u8> {
+    sorted.iter().copied().find(|&x| x >= v)
+}
+
+#[inline]
+fn last_at_or_before(sorted: &[u8], v: u8) -> Option<u8> {
+    sorted.iter().rev().copied().find(|&x| x <= v)
+}
+
+#[inline]
+fn prev_month(year: i32, month: u8) -> (i32, u8) {
+    if month == 1 {
+        (year - 1, 12)
+    } else {
+        (year, month - 1)
+    }
+}
+
+#[inline]
+fn prev_hour_of(year: i32, month: u8, day: u8, hour: u8) -> (i32, u8, u8, u8) {
+    if hour == 0 {
+        let (y, m, d) = prev_day_of(year, month, day);
+        (y, m, d, 23)
+    } else {
+        (year, month, day, hour - 1)
+    }
+}
+
+#[inline]
+fn prev_day_of(year: i32, month: u8, day: u8) -> (i32, u8, u8) {
+    if day == 1 {
+        let (y, m) = prev_month(year, month);
+        (y, m, days_in_month(y, m))
+    } else {
+        (year, month, day - 1)
+    }
+}
+
+#[inline]
+fn next_month(year: i32, month: u8) -> (i32, u8) {
+    if month == 12 {
+        (year + 1, 1)
+    } else {
+        (year, month + 1)
+    }
+}
+
+#[inline]
+fn next_hour_of(year: i32, month: u8, day: u8, hour: u8) -> (i32, u8, u8, u8) {
+    if hour == 23 {
+        let (y, m, d) = next_day_of(year, month, day);
+        (y, m, d, 0)
+    } else {
+        (year, month, day, hour + 1)
+    }
+}
+
+#[inline]
+fn next_day_of(year: i32, month: u8, day: u8) -> (i32, u8, u8) {
+    if day >= days_in_month(year, month) {
+        let (y, m) = next_month(year, month);
+        (y, m, 1)
+    } else {
+        (year, month, day + 1)
+    }
 }
 
+const MONTH_NAMES: [&str; 12] = [
+    "JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT", "NOV", "DEC",
+];
+const WEEKDAY_NAMES: [&str; 7] = ["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"];
+
+/// A piece of a cron expression and its byte offset, for error spans.
+type Token<'a> = (usize, &'a str);
+
 pub struct CronParser {
-    field_ranges: [(u8, u8); 5],
+    /// Resolves `H` tokens; usually `hash_id` of the job id.
+    seed: usize,
+}
+
+impl Default for CronParser {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl CronParser {
     pub fn new() -> Self {
-        Self {
-            field_ranges: [
-                (0, 59), // minute
-                (0, 23), // hour
-                (1, 31), // day
-                (1, 12), // month
-                (0, 6),  // weekday
-            ],
-        }
+        Self { seed: 0 }
+    }
+
+    /// Sets the seed `H` tokens are derived from. The same seed always
+    /// yields the same schedule.
+    pub fn set_seed(&mut self, seed: usize) {
+        self.seed = seed;
     }
 
-    pub fn parse(&self, expr: &str) -> Result<CronSchedule, String> {
-        let parts: Vec<&str> = expr.trim().split_whitespace().collect();
+    /// Parses a `schedule` value: a cron expression, a macro such as
+    /// `@daily`, `@every <duration>`, `@at <date time>` or `@reboot`.
+    pub fn parse_schedule(&self, expr: &str) -> Result<Schedule, CronError> {
+        let trimmed = expr.trim();
+        if let Some(rest) = trimmed.strip_prefix("@at") {
+            if rest.starts_with(char::is_whitespace) {
+                let value = rest.trim();
+                return DateTime::parse(value).map(Schedule::At).map_err(|_| {
+                    CronError::InvalidDateTime {
+                        value: value.to_string(),
+                        span: span_in(expr, value),
+                    }
+                });
+            }
+        }
+        if let Some(rest) = trimmed.strip_prefix("@every") {
+            if rest.starts_with(char::is_whitespace) {
+                let value = rest.trim();
+                let every = parse_duration(value)
+                    .ok()
+                    .filter(|&every| every <= MAX_INTERVAL)
+                    .ok_or_else(|| CronError::InvalidDuration {
+                        value: value.to_string(),
+                        span: span_in(expr, value),
+                    })?;
+                return Ok(Schedule::Interval(IntervalSchedule {
+                    every,
+                    anchor: None,
+                    exclude: Vec::new(),
+                }));
+            }
+        }
+
+        match trimmed {
+            "@reboot" => Ok(Schedule::Reboot),
+            _ => self.parse(expr).map(Schedule::Cron),
+        }
+    }
 
-        if parts.len() != 5 {
-            return Err(format!(
-                "Expected 5 fields in cron expression, got: {}",
-                parts.len()
-            ));
+    pub fn parse(&self, expr: &str) -> Result<CronSchedule, CronError> {
+        let trimmed = expr.trim();
+        if trimmed.starts_with('@') {
+            return match expand_macro(trimmed) {
+                Some(expanded) => self.parse(expanded),
+                None => Err(CronError::UnknownMacro {
+                    name: trimmed.to_string(),
+                    span: span_in(expr, trimmed),
+                }),
+            };
         }
 
-        let [minute_str, hour_str, day_str, month_str, weekday_str]: [&str; 5] =
-            parts.try_into().unwrap();
+        // Each field with its byte offset, for error spans
+        let parts: Vec<Token> = trimmed
+            .split_whitespace()
+            .map(|p| (span_in(expr, p).start, p))
+            .collect();
 
-        let minute_mask = self.parse_field(minute_str, self.field_ranges[0])?;
-        let hour_mask = self.parse_field(hour_str, self.field_ranges[1])? as u32;
-        let day_mask = self.parse_field(day_str, self.field_ranges[2])? as u32;
-        let month_mask = self.parse_field(month_str, self.field_ranges[3])? as u16;
-        let weekday_mask = self.parse_field(weekday_str, self.field_ranges[4])? as u8;
+        // An optional leading seconds field makes it 6, a trailing year 7
+        let (second, parts, year) = match parts.len() {
+            5 => ((0, "0"), &parts[..], (0, "*")),
+            6 => (parts[0], &parts[1..], (0, "*")),
+            7 => (parts[0], &parts[1..6], parts[6]),
+            found => {
+                return Err(CronError::FieldCount {
+                    found,
+                    span: span_in(expr, trimmed),
+                })
+            }
+        };
+
+        let [minute, hour, day, month, weekday]: [Token; 5] = parts.try_into().unwrap();
+
+        let second_mask = self.parse_field(&items(second), Field::Second, &[])?;
+        let minute_mask = self.parse_field(&items(minute), Field::Minute, &[])?;
+        let hour_mask = self.parse_field(&items(hour), Field::Hour, &[])? as u32;
+        let (day_plain, day_rules) = self.take_day_rules(day)?;
+        let day_mask = match day_plain.is_empty() {
+            true => 0,
+            false => self.parse_field(&day_plain, Field::Day, &[])? as u32,
+        };
+        let month_mask = self.parse_field(&items(month), Field::Month, &MONTH_NAMES)? as u16;
+        let (weekday_plain, weekday_rules) = self.take_weekday_rules(weekday)?;
+        let mut weekday_mask = match weekday_plain.is_empty() {
+            true => 0,
+            false => self.parse_field(&weekday_plain, Field::Weekday, &WEEKDAY_NAMES)?,
+        };
+
+        // 7 is an alias for Sunday
+        if weekday_mask & (1 << 7) != 0 {
+            weekday_mask = (weekday_mask & !(1 << 7)) | 1;
+        }
+        let weekday_mask = weekday_mask as u8;
 
         // Precompute sorted lists from masks (done once at load time)
+        let seconds = mask_to_list_u64(second_mask, 0, 59);
         let minutes = mask_to_list_u64(minute_mask, 0, 59);
         let hours = mask_to_list_u32(hour_mask, 0, 23);
         let days = mask_to_list_u32(day_mask, 1, 31);
         let months = mask_to_list_u16(month_mask, 1, 12);
         let weekdays = mask_to_list_u8(weekday_mask, 0, 6);
+        let years = self.parse_years(year)?;
 
         Ok(CronSchedule {
+            second: second_mask,
             minute: minute_mask,
             hour: hour_mask,
             day: day_mask,
             month: month_mask,
             weekday: weekday_mask,
+            seconds,
             minutes,
             hours,
             days,
             months,
             weekdays,
+            years,
+            day_rules,
+            weekday_rules,
+            day_restricted: day.1 != "*" && day.1 != "?",
+            weekday_restricted: weekday.1 != "*" && weekday.1 != "?",
+            day_match: DayMatch::Or,
+            exclude: Vec::new(),
         })
     }
 
-    fn parse_u8(&self, value: &str, err_msg: &str) -> Result<u8, String> {
-        match value.parse::<u8>() {
-            Ok(v) => Ok(v),
-            Err(_) => Err(err_msg.to_string()),
+    /// Parses the year field. Years do not fit the bitmasks, so this keeps
+    /// its own list; `*` and `?` mean any year.
+    fn parse_years(&self, field: Token) -> Result<Option<Vec<i32>>, CronError> {
+        if field.1 == "*" || field.1 == "?" {
+            return Ok(None);
+        }
+
+        let year = |s: &str, at: usize| -> Result<i32, CronError> {
+            let (value, span) = (s.to_string(), at..at + s.len());
+            match number::<i32>(s) {
+                Some(y) if (MIN_YEAR..=MAX_YEAR).contains(&y) => Ok(y),
+                Some(_) => Err(CronError::OutOfRange {
+                    field: Field::Year,
+                    value,
+                    span,
+                }),
+                None => Err(CronError::InvalidValue {
+                    field: Field::Year,
+                    value,
+                    span,
+                }),
+            }
+        };
+
+        let mut years = Vec::new();
+        for (at, part) in items(field) {
+            let (value, span) = (part.to_string(), at..at + part.len());
+            let (base, step) = match part.split_once('/') {
+                Some((base, step_str)) => match number::<usize>(step_str) {
+                    Some(step) if step > 0 => (base, Some(step)),
+                    _ => {
+                        return Err(CronError::InvalidStep {
+                            field: Field::Year,
+                            value,
+                            span,
+                        })
+                    }
+                },
+                None => (part, None),
+            };
+
+            let (a, b) = if base == "*" {
+                (MIN_YEAR, MAX_YEAR)
+            } else if let Some((a_str, b_str)) = base.split_once('-') {
+                let invalid_range = || CronError::InvalidRange {
+                    field: Field::Year,
+                    value: value.clone(),
+                    span: span.clone(),
+                };
+                let endpoint = |s: &str, at: usize| match year(s, at) {
+                    Err(CronError::InvalidValue { .. }) => Err(invalid_range()),
+                    other => other,
+                };
+                let (a, b) = (endpoint(a_str, at)?, endpoint(b_str, at + a_str.len() + 1)?);
+                if a > b {
+                    return Err(invalid_range());
+                }
+                (a, b)
+            } else {
+                let y = year(base, at)?;
+                match step {
+                    Some(_) => (y, MAX_YEAR),
+                    None => (y, y),
+                }
+            };
+
+            years.extend((a..=b).step_by(step.unwrap_or(1)));
+        }
+
+        years.sort_unstable();
+        years.dedup();
+        Ok(Some(years))
+    }
+
+    /// Pulls `L`, `L-n`, `LW` and `nW` out of a day-of-month field, returning
+    /// the remaining plain items (`?` becomes `*`) and the rules.
+    fn take_day_rules<'a>(
+        &self,
+        field: Token<'a>,
+    ) -> Result<(Vec<Token<'a>>, Vec<DayRule>), CronError> {
+        let mut plain = Vec::new();
+        let mut rules = Vec::new();
+
+        for (at, part) in items(field) {
+            let invalid = || CronError::InvalidValue {
+                field: Field::Day,
+                value: part.to_string(),
+                span: at..at + part.len(),
+            };
+            if part == "?" {
+                plain.push((at, "*"));
+            } else if part == "L" {
+                rules.push(DayRule::Last(0));
+            } else if part == "LW" {
+                rules.push(DayRule::LastWeekday);
+            } else if let Some(offset) = part.strip_prefix("L-") {
+                match number::<u8>(offset) {
+                    Some(offset) if offset <= 30 => rules.push(DayRule::Last(offset)),
+                    _ => return Err(invalid()),
+                }
+            } else if let Some(day) = part.strip_suffix('W').filter(|d| !d.is_empty()) {
+                let day = self.parse_value(day, at, Field::Day, &[])?;
+                rules.push(DayRule::NearestWeekday(day));
+            } else {
+                plain.push((at, part));
+            }
+        }
+
+        Ok((plain, rules))
+    }
+
+    /// Pulls `dL` and `d#n` out of a day-of-week field, returning the
+    /// remaining plain items (`?` becomes `*`) and the rules.
+    fn take_weekday_rules<'a>(
+        &self,
+        field: Token<'a>,
+    ) -> Result<(Vec<Token<'a>>, Vec<WeekdayRule>), CronError> {
+        let mut plain = Vec::new();
+        let mut rules = Vec::new();
+
+        // 7 is Sunday here too
+        let weekday = |s: &str, at: usize| -> Result<u8, CronError> {
+            Ok(self.parse_value(s, at, Field::Weekday, &WEEKDAY_NAMES)? % 7)
+        };
+
+        for (at, part) in items(field) {
+            if part == "?" {
+                plain.push((at, "*"));
+            } else if let Some((d, n)) = part.split_once('#') {
+                let d = weekday(d, at)?;
+                match number::<u8>(n) {
+                    Some(n) if (1..=5).contains(&n) => rules.push(WeekdayRule::Nth(d, n)),
+                    _ => {
+                        return Err(CronError::InvalidValue {
+                            field: Field::Weekday,
+                            value: part.to_string(),
+                            span: at..at + part.len(),
+                        })
+                    }
+                }
+            } else if let Some(d) = part.strip_suffix('L').filter(|d| !d.is_empty()) {
+                rules.push(WeekdayRule::Last(weekday(d, at)?));
+            } else {
+                plain.push((at, part));
+            }
+        }
+
+        Ok((plain, rules))
+    }
+
+    /// A number, or a case-insensitive name from `names` (first name = field
+    /// start), at byte `at` and within the field's bounds.
+    fn parse_value(
+        &self,
+        value: &str,
+        at: usize,
+        field: Field,
+        names: &[&str],
+    ) -> Result<u8, CronError> {
+        let (start, end) = field.bounds();
+        if let Some(idx) = names.iter().position(|n| n.eq_ignore_ascii_case(value)) {
+            return Ok(start as u8 + idx as u8);
+        }
+
+        let (text, span) = (value.to_string(), at..at + value.len());
+        match number::<i32>(value) {
+            Some(v) if (start..=end).contains(&v) => Ok(v as u8),
+            Some(_) => Err(CronError::OutOfRange {
+                field,
+                value: text,
+                span,
+            }),
+            None => Err(CronError::InvalidValue {
+                field,
+                value: text,
+                span,
+            }),
         }
     }
 
@@ -83,50 +1066,111 @@ impl CronParser {
         *mask |= 1 << bit;
     }
 
-    fn parse_field(&self, part: &str, field_range: (u8, u8)) -> Result<u64, String> {
+    /// Stable pseudo-random value in `0..span` for `field`, so each field of
+    /// a job spreads differently.
+    fn hashed(&self, field: Field, span: u8) -> u8 {
+        let field = field as u64;
+        let mut x = (self.seed as u64) ^ (field + 1).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+        x ^= x >> 33;
+        x = x.wrapping_mul(0xFF51_AFD7_ED55_8CCD);
+        x ^= x >> 33;
+        (x % span as u64) as u8
+    }
+
+    fn parse_field(&self, items: &[Token], field: Field, names: &[&str]) -> Result<u64, CronError> {
         let mut mask: u64 = 0;
-        let (start, end) = field_range;
-
-        for expr_part in part.split(',') {
-            if expr_part == "*" {
-                for v in start..=end {
-                    Self::set_bit(&mut mask, v);
-                }
-            } else if let Some(step_str) = expr_part.strip_prefix("*/") {
-                let step = self.parse_u8(step_str, &format!("Invalid step: {}", expr_part))?;
-                if step == 0 {
-                    return Err(format!("Invalid step: {}", expr_part));
-                }
-                let mut v = start;
-                while v <= end {
-                    Self::set_bit(&mut mask, v);
-                    match v.checked_add(step) {
-                        Some(next) => v = next,
-                        None => break,
+        let (start, end) = field.bounds();
+        let (start, end) = (start as u8, end as u8);
+
+        // 7 only exists as an alias for Sunday; open-ended steps stop at 6
+        let open_end = if end == 7 { 6 } else { end };
+
+        for &(at, expr_part) in items {
+            let invalid_range = || CronError::InvalidRange {
+                field,
+                value: expr_part.to_string(),
+                span: at..at + expr_part.len(),
+            };
+            // A missing or unreadable endpoint spoils the whole range; one
+            // outside the field is pointed at on its own
+            let endpoint = |s: &str, at: usize| match self.parse_value(s, at, field, names) {
+                Err(CronError::InvalidValue { .. }) => Err(invalid_range()),
+                other => other,
+            };
+            let (base, step) = match expr_part.split_once('/') {
+                Some((base, step_str)) => match number::<u8>(step_str) {
+                    Some(step) if step > 0 => (base, Some(step)),
+                    _ => {
+                        return Err(CronError::InvalidStep {
+                            field,
+                            value: expr_part.to_string(),
+                            span: at..at + expr_part.len(),
+                        })
                     }
+                },
+                None => (expr_part, None),
+            };
+
+            let (a, b) = if base == "*" {
+                match step {
+                    Some(_) => (start, open_end),
+                    None => (start, end),
                 }
-            } else if expr_part.contains('-') {
-                let parts: Vec<&str> = expr_part.split('-').collect();
-                if parts.len() != 2 {
-                    return Err(format!("Invalid range: {}", expr_part));
+            } else if let Some(hash_range) = base.strip_prefix('H') {
+                // `H`, `H(a-b)`, optionally `/step`: a job-specific value in range
+                let (lo, hi) = if hash_range.is_empty() {
+                    // A bare day stays within 1-28 so it fires every month
+                    (start, if field == Field::Day { 28 } else { open_end })
+                } else {
+                    let inner = hash_range
+                        .strip_prefix('(')
+                        .and_then(|r| r.strip_suffix(')'))
+                        .ok_or_else(invalid_range)?;
+                    let (a_str, b_str) = inner.split_once('-').ok_or_else(invalid_range)?;
+                    let a = endpoint(a_str, at + 2)?;
+                    let b = endpoint(b_str, at + 3 + a_str.len())?;
+                    if a > b {
+                        return Err(invalid_range());
+                    }
+                    (a, b)
+                };
+                match step {
+                    // `H/15` keeps the step but shifts its starting point
+                    Some(step) => (lo + self.hashed(field, step.min(hi - lo + 1)), hi),
+                    None => {
+                        let v = lo + self.hashed(field, hi - lo + 1);
+                        (v, v)
+                    }
                 }
-
-                let a = self.parse_u8(parts[0], &format!("Invalid range: {}", expr_part))?;
-                let b = self.parse_u8(parts[1], &format!("Invalid range: {}", expr_part))?;
-
-                if a > b || a < start || b > end {
-                    return Err(format!("Invalid range: {}", expr_part));
+            } else if base.contains('-') {
+                let parts: Vec<&str> = base.split('-').collect();
+                if parts.len() != 2 {
+                    return Err(invalid_range());
                 }
 
-                for v in a..=b {
-                    Self::set_bit(&mut mask, v);
+                let a = endpoint(parts[0], at)?;
+                let b = endpoint(parts[1], at + parts[0].len() + 1)?;
+                if a > b {
+                    return Err(invalid_range());
                 }
+                (a, b)
             } else {
-                let val = self.parse_u8(expr_part, &format!("Invalid value: {}", expr_part))?;
-                if val < start || val > end {
-                    return Err(format!("Invalid value: {}", expr_part));
+                let val = self.parse_value(base, at, field, names)?;
+                // `5/15` means "from 5 to the end of the field, every 15"
+                match step {
+                    Some(_) => (val, open_end.max(val)),
+                    None => (val, val),
+                }
+            };
+
+            let step = step.unwrap_or(1);
+            let mut v = a;
+            while v <= b {
+                Self::set_bit(&mut mask, v);
+                match v.checked_add(step) {
+                    Some(next) => v = next,
+                    None => break,
                 }
-                Self::set_bit(&mut mask, val);
             }
         }
 
@@ -134,6 +1178,248 @@ impl CronParser {
     }
 }
 
+/// Byte range of `part`, which must be a slice of `src`.
+fn span_in(src: &str, part: &str) -> Range<usize> {
+    let start = part.as_ptr() as usize - src.as_ptr() as usize;
+    start..start + part.len()
+}
+
+/// A plain run of ASCII digits. Cron numbers have no sign, so `+5` is not
+/// read as 5.
+fn number<T: std::str::FromStr>(s: &str) -> Option<T> {
+    if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    s.parse().ok()
+}
+
+/// The comma-separated items of a field at byte `at`, each with its offset.
+fn items((at, field): Token) -> Vec<Token> {
+    let mut offset = at;
+    field
+        .split(',')
+        .map(|item| {
+            let out = (offset, item);
+            offset += item.len() + 1;
+            out
+        })
+        .collect()
+}
+
+/// Parses a duration such as `90s`, `7m` or `1h30m` into seconds.
+/// Units are `d`, `h`, `m` and `s`; every number needs one.
+pub fn parse_duration(s: &str) -> Result<u64, String> {
+    match parse_duration_or_zero(s)? {
+        0 => Err(format!("Invalid duration: {}", s)),
+        secs => Ok(secs),
+    }
+}
+
+/// Like `parse_duration`, but also accepts a zero length such as `0s`.
+pub fn parse_duration_or_zero(s: &str) -> Result<u64, String> {
+    let err = || format!("Invalid duration: {}", s);
+    let mut total: u64 = 0;
+    let mut digits = String::new();
+
+    for c in s.chars() {
+        if c.is_ascii_digit() {
+            digits.push(c);
+            continue;
+        }
+        let unit = match c {
+            'd' => 86_400,
+            'h' => 3600,
+            'm' => 60,
+            's' => 1,
+            _ => return Err(err()),
+        };
+        let n: u64 = digits.parse().map_err(|_| err())?;
+        total = n
+            .checked_mul(unit)
+            .and_then(|v| total.checked_add(v))
+            .ok_or_else(err)?;
+        digits.clear();
+    }
+
+    if !digits.is_empty() || s.is_empty() {
+        return Err(err());
+    }
+    Ok(total)
+}
+
+/// Seconds as the `@every` notation, e.g. `1h30m`.
+pub fn format_duration(secs: u64) -> String {
+    let mut out = String::new();
+    let mut rest = secs;
+    for (unit, size) in [("d", 86_400), ("h", 3600), ("m", 60), ("s", 1)] {
+        if rest >= size {
+            out.push_str(&format!("{}{}", rest / size, unit));
+            rest %= size;
+        }
+    }
+    if out.is_empty() {
+        out.push_str("0s");
+    }
+    out
+}
+
+/// Canonical expression: numeric values, runs of three or more as ranges,
+/// full-width steps as `*/n`. The seconds field is only written when it is
+/// not just `0` (or a year field follows). `day_match` and `exclude` come
+/// from config keys and are not part of the expression.
+impl fmt::Display for CronSchedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut fields = Vec::with_capacity(7);
+        if self.seconds != [0] || self.years.is_some() {
+            fields.push(format_values(&widen(&self.seconds), 0, 59));
+        }
+        fields.push(format_values(&widen(&self.minutes), 0, 59));
+        fields.push(format_values(&widen(&self.hours), 0, 23));
+        fields.push(self.format_days());
+        fields.push(format_values(&widen(&self.months), 1, 12));
+        fields.push(self.format_weekdays());
+        if let Some(years) = &self.years {
+            let years: Vec<i64> = years.iter().map(|&y| y as i64).collect();
+            // `*` parses back to no year field at all
+            fields.push(
+                match format_values(&years, MIN_YEAR as i64, MAX_YEAR as i64) {
+                    all if all == "*" => format!("{}-{}", MIN_YEAR, MAX_YEAR),
+                    text => text,
+                },
+            );
+        }
+        f.write_str(&fields.join(" "))
+    }
+}
+
+impl CronSchedule {
+    fn format_days(&self) -> String {
+        if !self.day_restricted {
+            return "*".to_string();
+        }
+        // A restricted but complete field must not collapse to `*`, which
+        // would change how it combines with the weekday field
+        let mut parts = match self.days.len() {
+            0 => Vec::new(),
+            31 => vec!["1-31".to_string()],
+            _ => vec![format_values(&widen(&self.days), 1, 31)],
+        };
+        for rule in &self.day_rules {
+            parts.push(match *rule {
+                DayRule::Last(0) => "L".to_string(),
+                DayRule::Last(n) => format!("L-{}", n),
+                DayRule::LastWeekday => "LW".to_string(),
+                DayRule::NearestWeekday(d) => format!("{}W", d),
+            });
+        }
+        parts.join(",")
+    }
+
+    fn format_weekdays(&self) -> String {
+        if !self.weekday_restricted {
+            return "*".to_string();
+        }
+        let mut parts = match self.weekdays.len() {
+            0 => Vec::new(),
+            7 => vec!["0-6".to_string()],
+            _ => vec![format_values(&widen(&self.weekdays), 0, 6)],
+        };
+        for rule in &self.weekday_rules {
+            parts.push(match *rule {
+                WeekdayRule::Last(w) => format!("{}L", w),
+                WeekdayRule::Nth(w, n) => format!("{}#{}", w, n),
+            });
+        }
+        parts.join(",")
+    }
+}
+
+impl FromStr for CronSchedule {
+    type Err = CronError;
+
+    fn from_str(s: &str) -> Result<Self, CronError> {
+        CronParser::new().parse(s)
+    }
+}
+
+impl fmt::Display for Schedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Schedule::Cron(cron) => cron.fmt(f),
+            Schedule::Interval(interval) => write!(f, "@every {}", format_duration(interval.every)),
+            Schedule::At(at) => write!(
+                f,
+                "@at {:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+                at.year, at.month, at.day, at.hour, at.minute, at.second
+            ),
+            Schedule::Reboot => f.write_str("@reboot"),
+        }
+    }
+}
+
+impl FromStr for Schedule {
+    type Err = CronError;
+
+    fn from_str(s: &str) -> Result<Self, CronError> {
+        CronParser::new().parse_schedule(s)
+    }
+}
+
+#[inline]
+fn widen(values: &[u8]) -> Vec<i64> {
+    values.iter().map(|&v| v as i64).collect()
+}
+
+/// One field from its sorted values within `lo..=hi`.
+fn format_values(values: &[i64], lo: i64, hi: i64) -> String {
+    if values.len() as i64 == hi - lo + 1 {
+        return "*".to_string();
+    }
+
+    // `start/step` running to the end of the field
+    if values.len() >= 2 {
+        let step = values[1] - values[0];
+        let regular = values.windows(2).all(|w| w[1] - w[0] == step);
+        let reaches_end = values[values.len() - 1] + step > hi;
+        if step > 1 && regular && reaches_end {
+            if values[0] == lo {
+                return format!("*/{}", step);
+            }
+            if values.len() >= 3 {
+                return format!("{}/{}", values[0], step);
+            }
+        }
+    }
+
+    let mut parts = Vec::new();
+    let mut i = 0;
+    while i < values.len() {
+        let mut j = i;
+        while j + 1 < values.len() && values[j + 1] == values[j] + 1 {
+            j += 1;
+        }
+        if j - i >= 2 {
+            parts.push(format!("{}-{}", values[i], values[j]));
+        } else {
+            parts.extend(values[i..=j].iter().map(|v| v.to_string()));
+        }
+        i = j + 1;
+    }
+    parts.join(",")
+}
+
+/// Cron equivalent of a predefined `@macro`.
+fn expand_macro(name: &str) -> Option<&'static str> {
+    match name {
+        "@yearly" | "@annually" => Some("0 0 1 1 *"),
+        "@monthly" => Some("0 0 1 * *"),
+        "@weekly" => Some("0 0 * * 0"),
+        "@daily" | "@midnight" => Some("0 0 * * *"),
+        "@hourly" => Some("0 * * * *"),
+        _ => None,
+    }
+}
+
 #[inline]
 fn mask_to_list_u64(mask: u64, start: u8, end: u8) -> Vec<u8> {
     let mut out = Vec::new();
@@ -1,28 +1,76 @@
 use std::cmp::Ordering;
-use std::collections::BinaryHeap;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::fs;
+use std::io::{BufRead, BufReader, Read};
 use std::path::Path;
-use std::process::{Command, Stdio};
+use std::process::Stdio;
+use std::sync::mpsc::{self, Receiver, Sender};
 use std::sync::Arc;
 use std::thread;
-use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use crate::cli_colors::CliColorPicker;
+use crate::datetime::{system_time_to_unix, unix_to_system_time};
+use crate::error::ConfigErrors;
 use crate::loader::ConfigCache;
-use crate::models::JobSpec;
-use crate::parser::CronSchedule;
+use crate::models::{JobInstance, JobSpec, Overlap};
+use crate::parser::{CronSchedule, DstPolicy, Schedule};
 use crate::printer::Printer;
+use crate::state::FiredLog;
+use crate::supervisor::{self, Stopper};
+use crate::tz::TimeZone;
+
+/// Longest the loop sleeps before checking for a shutdown request.
+const POLL: Duration = Duration::from_millis(250);
+
+/// Longest a finished run's report waits for the rest of its output.
+const OUTPUT_DRAIN: Duration = Duration::from_millis(500);
 
 pub struct Scheduler {
     queue: BinaryHeap<ScheduledJob>,
     cache: ConfigCache,
     printer: Printer,
     colors: CliColorPicker,
+    /// Default anchor for interval schedules.
+    started: i64,
+    fired: FiredLog,
+    /// Runs still in flight, per job id.
+    running: HashMap<String, Vec<InFlight>>,
+    /// Runs held back by `overlap = queue`, or by `overlap = replace` until
+    /// the run they replace has ended, per job id.
+    queued: HashMap<String, Arc<JobSpec>>,
+    /// Instances waiting for a free slot under `max_concurrent` or
+    /// `max_parallel`, oldest first.
+    pending: VecDeque<Pending>,
+    next_run: u64,
+    done_tx: Sender<Done>,
+    done_rx: Receiver<Done>,
+}
+
+/// One spawned instance of a job.
+struct InFlight {
+    run: u64,
+    stopper: Stopper,
+}
+
+/// An instance that is due but not started yet.
+struct Pending {
+    job: Arc<JobSpec>,
+    instance: JobInstance,
+}
+
+/// Sent by a run's supervising thread once it has been reaped.
+struct Done {
+    job_id: String,
+    run: u64,
 }
 
 #[derive(Clone)]
 struct ScheduledJob {
-    when: Instant,
+    when: SystemTime,
     job: Arc<JobSpec>,
+    /// Set once the job's random delay has been added to `when`.
+    jittered: bool,
 }
 
 impl PartialEq for ScheduledJob {
@@ -33,7 +81,7 @@ impl PartialEq for ScheduledJob {
 impl Eq for ScheduledJob {}
 impl PartialOrd for ScheduledJob {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(other.when.cmp(&self.when))
+        Some(self.cmp(other))
     }
 }
 impl Ord for ScheduledJob {
@@ -44,114 +92,474 @@ impl Ord for ScheduledJob {
 
 impl Scheduler {
     pub fn new(cache: ConfigCache, printer: Printer) -> Self {
+        let (done_tx, done_rx) = mpsc::channel();
         Self {
             queue: BinaryHeap::new(),
             cache,
             printer,
             colors: CliColorPicker::new(),
+            started: system_time_to_unix(SystemTime::now()),
+            fired: FiredLog::in_memory(),
+            running: HashMap::new(),
+            queued: HashMap::new(),
+            pending: VecDeque::new(),
+            next_run: 0,
+            done_tx,
+            done_rx,
         }
     }
 
+    /// Persists `@at` runs so they survive restarts. Call before `init`.
+    pub fn set_fired_log(&mut self, fired: FiredLog) {
+        self.fired = fired;
+    }
+
     pub fn init(&mut self) {
-        self.queue.clear();
+        // Runs already due and waiting out their jitter delay survive a
+        // reload, as long as their job does. Firing schedules the job's
+        // next run.
+        let delayed: Vec<ScheduledJob> = self.queue.drain().filter(|s| s.jittered).collect();
         for job in &self.cache.jobs {
-            self.queue.push(ScheduledJob {
-                when: compute_next_run(&job.schedule),
-                job: Arc::new(job.clone()),
-            });
+            if let Some(sched_job) = delayed.iter().find(|s| s.job.id == job.id) {
+                self.queue.push(ScheduledJob {
+                    when: sched_job.when,
+                    job: Arc::new(job.clone()),
+                    jittered: true,
+                });
+            } else if let Some(when) = next_due(job, self.started, &self.fired) {
+                self.queue.push(ScheduledJob {
+                    when,
+                    job: Arc::new(job.clone()),
+                    jittered: false,
+                });
+            } else {
+                match &job.schedule {
+                    Schedule::At(at) => {
+                        if !self.fired.contains(&job.id, job.timezone.to_utc(at)) {
+                            self.printer.write(format!(
+                                "[{}] not scheduled: {:04}-{:02}-{:02} {:02}:{:02}:{:02} has already passed",
+                                job.id, at.year, at.month, at.day, at.hour, at.minute, at.second
+                            ));
+                        }
+                    }
+                    Schedule::Cron(_) | Schedule::Interval(_) => {
+                        self.printer.write(format!(
+                            "[{}] not scheduled: it can never fire again",
+                            job.id
+                        ));
+                    }
+                    Schedule::Reboot => {}
+                }
+            }
         }
     }
 
+    /// Runs jobs until croner is asked to shut down, then stops them.
     pub fn run(&mut self, config_path: &Path) {
+        self.run_startup_jobs();
+
         loop {
-            if let Ok(true) = self.cache.reload_if_changed(config_path) {
-                self.init();
+            if supervisor::shutdown_requests() > 0 {
+                self.shutdown();
+                return;
+            }
+
+            match self.cache.reload_if_changed(config_path) {
+                Ok(true) => self.init(),
+                Ok(false) => {}
+                Err(errors) => self.report_reload_errors(config_path, &errors),
             }
 
             if let Some(sched_job) = self.queue.pop() {
-                let now = Instant::now();
-                if sched_job.when <= now {
-                    self.run_job(&sched_job.job);
+                let now = SystemTime::now();
+                if sched_job.when <= now && !sched_job.jittered && !sched_job.job.jitter.is_zero() {
+                    // Due: push back once by a random share of the jitter window
                     self.queue.push(ScheduledJob {
-                        when: compute_next_run(&sched_job.job.schedule),
-                        job: sched_job.job.clone(),
+                        when: sched_job.when + splay(&sched_job.job.id, sched_job.job.jitter),
+                        jittered: true,
+                        ..sched_job
                     });
+                } else if sched_job.when <= now {
+                    self.dispatch(&sched_job.job);
+                    match next_due(&sched_job.job, self.started, &self.fired) {
+                        Some(when) => self.queue.push(ScheduledJob {
+                            when,
+                            job: sched_job.job.clone(),
+                            jittered: false,
+                        }),
+                        None if matches!(sched_job.job.schedule, Schedule::Reboot) => {}
+                        None => self.printer.write(format!(
+                            "[{}] removed from schedule: it can never fire again",
+                            sched_job.job.id
+                        )),
+                    }
                 } else {
-                    let sleep_dur = sched_job.when.saturating_duration_since(now);
-                    thread::sleep(sleep_dur);
+                    let sleep_dur = sched_job.when.duration_since(now).unwrap_or_default();
                     self.queue.push(sched_job);
+                    self.wait_for_runs(sleep_dur);
+                }
+            } else {
+                self.wait_for_runs(Duration::from_secs(1));
+            }
+        }
+    }
+
+    /// Prints why a changed config was rejected. The jobs already loaded
+    /// keep running.
+    fn report_reload_errors(&self, path: &Path, errors: &ConfigErrors) {
+        let source = fs::read_to_string(path).unwrap_or_default();
+        self.printer.write(match errors.errors().len() {
+            1 => format!(
+                "Failed to reload config, keeping the current jobs: {}",
+                errors.render(&source)
+            ),
+            n => format!(
+                "Failed to reload config, keeping the current jobs: {} errors\n\n{}",
+                n,
+                errors.render(&source)
+            ),
+        });
+    }
+
+    /// Sleeps up to `timeout`, waking early to account for finished runs.
+    /// Never longer than `POLL`, so a shutdown request is noticed quickly.
+    fn wait_for_runs(&mut self, timeout: Duration) {
+        if let Ok(done) = self.done_rx.recv_timeout(timeout.min(POLL)) {
+            self.finished(done);
+            while let Ok(done) = self.done_rx.try_recv() {
+                self.finished(done);
+            }
+        }
+    }
+
+    /// Stops every run in flight and waits for them to end. Runs that
+    /// haven't started are dropped. Another shutdown request kills whatever
+    /// is still in its grace period.
+    fn shutdown(&mut self) {
+        self.pending.clear();
+        self.queued.clear();
+        let count: usize = self.running.values().map(Vec::len).sum();
+        if count == 0 {
+            return;
+        }
+        self.printer
+            .write(format!("Shutting down: stopping {} running job(s)", count));
+        for run in self.running.values().flatten() {
+            run.stopper.stop();
+        }
+
+        let mut killed = false;
+        while !self.running.is_empty() {
+            if !killed && supervisor::shutdown_requests() > 1 {
+                for run in self.running.values().flatten() {
+                    run.stopper.kill();
+                }
+                killed = true;
+            }
+            self.wait_for_runs(POLL);
+        }
+    }
+
+    fn finished(&mut self, done: Done) {
+        let Some(runs) = self.running.get_mut(&done.job_id) else {
+            return;
+        };
+        runs.retain(|r| r.run != done.run);
+        if runs.is_empty() {
+            self.running.remove(&done.job_id);
+        }
+
+        // The freed slot goes to whatever has waited longest
+        self.start_pending();
+        if self.is_active(&done.job_id) {
+            return;
+        }
+        if let Some(job) = self.queued.remove(&done.job_id) {
+            let kind = match job.overlap {
+                Overlap::Replace => "replacement",
+                _ => "queued",
+            };
+            self.printer
+                .write(format!("[{}] starting {} run", done.job_id, kind));
+            self.run_job(&job);
+        }
+    }
+
+    /// Whether a run of the job is in flight or waiting for a slot.
+    fn is_active(&self, id: &str) -> bool {
+        self.running.contains_key(id) || self.pending.iter().any(|p| p.job.id == id)
+    }
+
+    /// Starts a due run, unless the job's overlap policy holds it back.
+    fn dispatch(&mut self, job: &Arc<JobSpec>) {
+        let active = self.is_active(&job.id);
+        let queued = self.queued.contains_key(&job.id);
+        match overlap_action(job.overlap, active, queued) {
+            OverlapAction::Start => {}
+            OverlapAction::Skip => {
+                self.printer
+                    .write(format!("[{}] skipped: previous run still active", job.id));
+                return;
+            }
+            OverlapAction::Queue => {
+                self.printer
+                    .write(format!("[{}] queued: previous run still active", job.id));
+                self.queued.insert(job.id.clone(), job.clone());
+                return;
+            }
+            OverlapAction::SkipQueued => {
+                self.printer
+                    .write(format!("[{}] skipped: a run is already queued", job.id));
+                return;
+            }
+            OverlapAction::Replace => {
+                self.printer
+                    .write(format!("[{}] replacing previous run still active", job.id));
+                self.pending.retain(|p| p.job.id != job.id);
+                if let Some(runs) = self.running.get(&job.id) {
+                    for run in runs {
+                        run.stopper.stop();
+                    }
+                    // Both copies must never run at once; this one starts
+                    // once the last of the old ones has been reaped
+                    self.queued.insert(job.id.clone(), job.clone());
+                    return;
                 }
+            }
+        }
+        self.run_job(job);
+    }
+
+    /// Fires `@reboot` jobs, after their jitter delay if they have one.
+    /// Only called once per process, so hot reloads never re-run them.
+    fn run_startup_jobs(&mut self) {
+        let startup: Vec<Arc<JobSpec>> = self
+            .cache
+            .jobs
+            .iter()
+            .filter(|job| matches!(job.schedule, Schedule::Reboot))
+            .map(|job| Arc::new(job.clone()))
+            .collect();
+
+        let now = SystemTime::now();
+        for job in startup {
+            if !in_window(&job, now) {
+                self.printer.write(format!(
+                    "[{}] not run: croner started outside its not_before/not_after window",
+                    job.id
+                ));
+            } else if job.jitter.is_zero() {
+                self.run_job(&job);
             } else {
-                thread::sleep(Duration::from_secs(1));
+                self.queue.push(ScheduledJob {
+                    when: now + splay(&job.id, job.jitter),
+                    job,
+                    jittered: true,
+                });
             }
         }
     }
 
+    /// Queues every instance of the job and starts as many as the limits
+    /// allow.
     fn run_job(&mut self, job: &Arc<JobSpec>) {
+        // Only a one-shot that actually runs is done with; one dropped by
+        // its overlap policy is reported as missed on the next start
+        if let Schedule::At(at) = &job.schedule {
+            // The configured instant, not the jittered one
+            let at = job.timezone.to_utc(at);
+            if let Err(e) = self.fired.record(&job.id, at) {
+                self.printer.write(format!("[{}] {}", job.id, e));
+            }
+        }
+
         let instances = job.expand();
-        let color_code = self.colors.get(hash_id(&job.id));
-
-        for instance in instances {
-            // Join all parts of the command into a single string
-            let full_cmd = instance
-                .command
-                .iter()
-                .map(|s| s.to_string_lossy())
-                .collect::<Vec<_>>()
-                .join(" ");
-
-            #[cfg(unix)]
-            let mut cmd = Command::new("sh");
-            #[cfg(unix)]
-            cmd.arg("-c").arg(&full_cmd);
-
-            #[cfg(windows)]
-            let mut cmd = Command::new("cmd");
-            #[cfg(windows)]
-            cmd.arg("/C").arg(&full_cmd);
-
-            cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
-
-            let job_id = instance.id.clone();
-            let printer = self.printer.clone();
-            let color = color_code.to_string();
-
-            match cmd.spawn() {
-                Ok(mut child) => {
-                    if let Some(stdout) = child.stdout.take() {
-                        let p = printer.clone();
-                        let c = color.clone();
-                        let jid = job_id.clone();
-                        thread::spawn(move || {
-                            use std::io::{BufRead, BufReader};
-                            for line in BufReader::new(stdout).lines().flatten() {
-                                p.write(format!("{}[{}]\u{1b}[0m {}", c, jid, line));
-                            }
-                        });
-                    }
-                    if let Some(stderr) = child.stderr.take() {
-                        let p = printer.clone();
-                        let c = color.clone();
-                        let jid = job_id.clone();
-                        thread::spawn(move || {
-                            use std::io::{BufRead, BufReader};
-                            for line in BufReader::new(stderr).lines().flatten() {
-                                p.write(format!("{}[{}]\u{1b}[0m {}", c, jid, line));
-                            }
-                        });
-                    }
+        let total = instances.len();
+        self.pending
+            .extend(instances.into_iter().map(|instance| Pending {
+                job: job.clone(),
+                instance,
+            }));
+        self.start_pending();
+
+        let waiting = self.pending.iter().filter(|p| p.job.id == job.id).count();
+        if waiting > 0 {
+            self.printer.write(format!(
+                "[{}] {} of {} instances waiting for a free slot",
+                job.id, waiting, total
+            ));
+        }
+    }
+
+    /// Starts waiting instances, oldest first, while `max_concurrent` and
+    /// each job's `max_parallel` allow.
+    fn start_pending(&mut self) {
+        let mut running: HashMap<String, usize> = self
+            .running
+            .iter()
+            .map(|(id, runs)| (id.clone(), runs.len()))
+            .collect();
+        let waiting = std::mem::take(&mut self.pending);
+        let max_concurrent = self.cache.max_concurrent;
+        self.pending = start_in_order(
+            waiting,
+            &mut running,
+            max_concurrent,
+            |p| (&p.job.id, p.job.max_parallel),
+            |p| self.spawn(&p.job, p.instance),
+        );
+    }
+
+    /// Starts one instance, reporting whether it is now running.
+    fn spawn(&mut self, job: &Arc<JobSpec>, instance: JobInstance) -> bool {
+        let color = self.colors.get(hash_id(&job.id));
+        let job_id = instance.id;
+        let printer = self.printer.clone();
+
+        let Some(mut cmd) = job.shell.command(&instance.command) else {
+            self.printer.write(format!(
+                "{}[{}]\u{1b}[0m failed to start: empty command",
+                color, job_id
+            ));
+            return false;
+        };
+        // Jobs are outside the terminal's foreground group; reading it would
+        // stop them with SIGTTIN
+        cmd.stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+
+        match supervisor::spawn(&mut cmd) {
+            Ok(mut process) => {
+                self.next_run += 1;
+                let run = self.next_run;
+                self.running
+                    .entry(job.id.clone())
+                    .or_default()
+                    .push(InFlight {
+                        run,
+                        stopper: process.stopper(),
+                    });
+
+                let prefix = format!("{}[{}]\u{1b}[0m", color, job_id);
+                // Disconnects once both readers are done
+                let (open, closed) = mpsc::channel::<()>();
+                if let Some(stdout) = process.child.stdout.take() {
+                    forward_lines(stdout, printer.clone(), prefix.clone(), open.clone());
                 }
-                Err(e) => {
-                    self.printer.write(format!(
-                        "{}[{}]\u{1b}[0m failed to start: {}",
-                        color, job_id, e
-                    ));
+                if let Some(stderr) = process.child.stderr.take() {
+                    forward_lines(stderr, printer.clone(), prefix.clone(), open);
                 }
+
+                // Reap the child so it can't linger as a zombie. Its slot is
+                // free from then on, even if something it started in the
+                // background still holds the pipes
+                let (timeout, grace) = (job.timeout, job.kill_grace);
+                let done = self.done_tx.clone();
+                let spec_id = job.id.clone();
+                thread::spawn(move || {
+                    let status = process.wait(timeout, grace);
+                    let _ = done.send(Done {
+                        job_id: spec_id,
+                        run,
+                    });
+                    // Let the last lines come first, but don't wait on pipes
+                    // that a background process keeps open
+                    let _ = closed.recv_timeout(OUTPUT_DRAIN);
+                    match status {
+                        Ok(status) => printer.write(format!("{} {}", prefix, status)),
+                        Err(e) => printer.write(format!("{} wait failed: {}", prefix, e)),
+                    }
+                });
+                true
+            }
+            Err(e) => {
+                self.printer.write(format!(
+                    "{}[{}]\u{1b}[0m failed to start: {}",
+                    color, job_id, e
+                ));
+                false
             }
         }
     }
 }
 
+/// Prints each line `reader` yields after `prefix` until it closes, then
+/// drops `open`.
+fn forward_lines<R: Read + Send + 'static>(
+    reader: R,
+    printer: Printer,
+    prefix: String,
+    open: Sender<()>,
+) {
+    thread::spawn(move || {
+        for line in BufReader::new(reader).lines().map_while(Result::ok) {
+            printer.write(format!("{} {}", prefix, line));
+        }
+        drop(open);
+    });
+}
+
+/// Starts `waiting` instances, oldest first, as long as `max_concurrent`
+/// and each one's `max_parallel` allow, and returns those left waiting.
+/// `running` counts the runs in flight per job id, `limit` gives an
+/// instance's job id and `max_parallel`, and `start` whether it started.
+pub fn start_in_order<T>(
+    waiting: VecDeque<T>,
+    running: &mut HashMap<String, usize>,
+    max_concurrent: Option<usize>,
+    limit: impl Fn(&T) -> (&str, Option<usize>),
+    mut start: impl FnMut(T) -> bool,
+) -> VecDeque<T> {
+    let mut left = VecDeque::new();
+    for item in waiting {
+        let (id, max_parallel) = limit(&item);
+        let total: usize = running.values().sum();
+        let own = running.get(id).copied().unwrap_or(0);
+        if max_concurrent.is_some_and(|max| total >= max)
+            || max_parallel.is_some_and(|max| own >= max)
+        {
+            left.push_back(item);
+            continue;
+        }
+        let id = id.to_string();
+        if start(item) {
+            *running.entry(id).or_default() += 1;
+        }
+    }
+    left
+}
+
+/// What becomes of a run that falls due.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverlapAction {
+    Start,
+    /// Drop it; an earlier run is still active.
+    Skip,
+    /// Hold it until the earlier run ends.
+    Queue,
+    /// Drop it; another run is already held back.
+    SkipQueued,
+    /// Stop the earlier run, and start this one once it has ended.
+    Replace,
+}
+
+/// Applies the job's overlap policy to a due run. `active` is whether an
+/// earlier run is in flight or waiting for a slot, `queued` whether one is
+/// already held back by `overlap = queue`.
+pub fn overlap_action(policy: Overlap, active: bool, queued: bool) -> OverlapAction {
+    if !active {
+        return OverlapAction::Start;
+    }
+    match policy {
+        Overlap::Allow => OverlapAction::Start,
+        Overlap::Skip => OverlapAction::Skip,
+        Overlap::Queue if queued => OverlapAction::SkipQueued,
+        Overlap::Queue => OverlapAction::Queue,
+        Overlap::Replace => OverlapAction::Replace,
+    }
+}
+
 /// Very fast hash for job IDs → color slot
 pub fn hash_id(id: &str) -> usize {
     let mut hash = 0usize;
@@ -161,31 +569,76 @@ pub fn hash_id(id: &str) -> usize {
     hash
 }
 
-pub fn compute_next_run(schedule: &CronSchedule) -> Instant {
-    let now = SystemTime::now()
+/// Random delay in `0..=max`, at millisecond resolution. Seeded from the
+/// clock and the job id so jobs popped together still get different delays.
+fn splay(id: &str, max: Duration) -> Duration {
+    let nanos = SystemTime::now()
         .duration_since(UNIX_EPOCH)
-        .unwrap_or_default();
+        .unwrap_or_default()
+        .subsec_nanos() as u64;
 
-    let secs = now.as_secs();
-    let minutes = secs / 60;
-    let mut next_minutes = minutes + 1;
+    let mut x = nanos ^ (hash_id(id) as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    x ^= x >> 33;
+    x = x.wrapping_mul(0xFF51_AFD7_ED55_8CCD);
+    x ^= x >> 33;
 
-    loop {
-        let total_minutes = next_minutes;
-        let minute = (total_minutes % 60) as u8;
-        let hour = ((total_minutes / 60) % 24) as u8;
-        let days_since_epoch = total_minutes / (60 * 24);
+    let max = u64::try_from(max.as_millis()).unwrap_or(u64::MAX);
+    Duration::from_millis(x % max.saturating_add(1))
+}
 
-        let weekday = ((days_since_epoch + 4) % 7) as u8;
+/// When a job should next be pushed onto the heap. `@reboot` jobs never are,
+/// and `@at` jobs only until they fire or their time passes. Runs outside
+/// `not_before..=not_after` are never returned.
+fn next_due(job: &JobSpec, started: i64, fired: &FiredLog) -> Option<SystemTime> {
+    let now = SystemTime::now();
+    // Search from just before the window opens rather than from now
+    let after = match job.not_before {
+        Some(nb) if nb > now => nb - Duration::from_secs(1),
+        _ => now,
+    };
 
-        if (schedule.minute & (1 << minute) != 0)
-            && (schedule.hour & (1 << hour) != 0)
-            && (schedule.weekday & (1 << weekday) != 0)
-        {
-            let delta_secs = (next_minutes - minutes) * 60;
-            return Instant::now() + Duration::from_secs(delta_secs);
+    let when = match &job.schedule {
+        Schedule::Cron(cron) => next_run_after(cron, &job.timezone, job.dst_policy, after),
+        Schedule::Interval(interval) => {
+            let after = system_time_to_unix(after);
+            interval
+                .next_after_in(after, started, &job.timezone)
+                .map(unix_to_system_time)
+        }
+        Schedule::At(at) => {
+            let at = job.timezone.to_utc(at);
+            let now = system_time_to_unix(now);
+            (at > now && !fired.contains(&job.id, at)).then(|| unix_to_system_time(at))
         }
+        Schedule::Reboot => None,
+    }?;
 
-        next_minutes += 1;
-    }
+    in_window(job, when).then_some(when)
+}
+
+/// Whether `when` falls inside the job's `not_before..=not_after` window.
+fn in_window(job: &JobSpec, when: SystemTime) -> bool {
+    job.not_before.map_or(true, |nb| when >= nb) && job.not_after.map_or(true, |na| when <= na)
+}
+
+/// Next wall-clock time the schedule fires in `tz`, strictly after now.
+/// `None` if the schedule can never match (e.g. `0 0 31 2 *`).
+pub fn compute_next_run(
+    schedule: &CronSchedule,
+    tz: &TimeZone,
+    policy: DstPolicy,
+) -> Option<SystemTime> {
+    next_run_after(schedule, tz, policy, SystemTime::now())
+}
+
+/// Next wall-clock time the schedule fires in `tz`, strictly after `after`.
+pub fn next_run_after(
+    schedule: &CronSchedule,
+    tz: &TimeZone,
+    policy: DstPolicy,
+    after: SystemTime,
+) -> Option<SystemTime> {
+    schedule
+        .next_after_in(system_time_to_unix(after), tz, policy)
+        .map(unix_to_system_time)
 }
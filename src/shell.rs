@@ -1,4 +1,51 @@
 use std::ffi::OsString;
+use std::process::Command;
+
+/// Interpreter a job's command line is handed to. With `None` the command
+/// is split into argv here and run directly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Shell {
+    #[default]
+    None,
+    Sh,
+    Bash,
+    Cmd,
+    PowerShell,
+}
+
+impl Shell {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "none" => Some(Self::None),
+            "sh" => Some(Self::Sh),
+            "bash" => Some(Self::Bash),
+            "cmd" => Some(Self::Cmd),
+            "powershell" => Some(Self::PowerShell),
+            _ => None,
+        }
+    }
+
+    /// Process for one run. Without a shell `argv` is the program and its
+    /// arguments; with one it holds the whole command line as one element.
+    /// `None` if there is nothing to run.
+    pub fn command(self, argv: &[OsString]) -> Option<Command> {
+        let (program, rest) = argv.split_first()?;
+        let (shell, flags): (&str, &[&str]) = match self {
+            Shell::None => {
+                let mut cmd = Command::new(program);
+                cmd.args(rest);
+                return Some(cmd);
+            }
+            Shell::Sh => ("sh", &["-c"]),
+            Shell::Bash => ("bash", &["-c"]),
+            Shell::Cmd => ("cmd", &["/C"]),
+            Shell::PowerShell => ("powershell", &["-NoProfile", "-Command"]),
+        };
+        let mut cmd = Command::new(shell);
+        cmd.args(flags).args(argv);
+        Some(cmd)
+    }
+}
 
 #[inline]
 pub fn get_command(input: &str) -> Vec<String> {
@@ -14,6 +61,14 @@ pub fn get_command_as_os_str(input: &str) -> Vec<OsString> {
         .collect()
 }
 
+/// Like `get_command_as_os_str`, but reports why a command can't be split.
+pub fn try_get_command_as_os_str(input: &str) -> Result<Vec<OsString>, &'static str> {
+    Ok(parse_command(input)?
+        .into_iter()
+        .map(OsString::from)
+        .collect())
+}
+
 #[inline]
 fn parse_command(input: &str) -> Result<Vec<String>, &'static str> {
     let input_bytes = input.as_bytes();
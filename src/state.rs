@@ -0,0 +1,97 @@
+use std::collections::HashSet;
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+
+/// Remembers which one-shot (`@at`) jobs already fired, so neither a restart
+/// nor a hot reload runs them again. Stored as one `<unix> <job id>` line
+/// per run, next to the config file.
+#[derive(Debug, Default)]
+pub struct FiredLog {
+    path: Option<PathBuf>,
+    fired: HashSet<(String, i64)>,
+    /// Line numbers `open` could not read, such as a write torn by a crash.
+    skipped: Vec<usize>,
+    /// Set while the file ends partway through a line.
+    torn: bool,
+}
+
+impl FiredLog {
+    /// A log that is never written to disk.
+    pub fn in_memory() -> Self {
+        Self::default()
+    }
+
+    /// State file used for a given config: `<config>.state`.
+    pub fn path_for(config: &Path) -> PathBuf {
+        let mut name = config.as_os_str().to_owned();
+        name.push(".state");
+        PathBuf::from(name)
+    }
+
+    /// Loads the log at `path`. A missing file is an empty log, and lines
+    /// that can't be read are skipped; see `skipped_lines`.
+    pub fn open(path: &Path) -> Result<Self, String> {
+        let text = match fs::read_to_string(path) {
+            Ok(t) => t,
+            Err(e) if e.kind() == ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(format!("failed to read state: {}", e)),
+        };
+
+        let mut fired = HashSet::new();
+        let mut skipped = Vec::new();
+        for (i, line) in text.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let entry = line
+                .split_once(' ')
+                .and_then(|(at, id)| Some((id.to_string(), at.parse().ok()?)));
+            match entry {
+                Some(e) => {
+                    fired.insert(e);
+                }
+                None => skipped.push(i + 1),
+            }
+        }
+
+        Ok(Self {
+            path: Some(path.to_path_buf()),
+            fired,
+            skipped,
+            torn: !text.is_empty() && !text.ends_with('\n'),
+        })
+    }
+
+    /// Lines of the file `open` skipped as malformed, numbered from 1.
+    pub fn skipped_lines(&self) -> &[usize] {
+        &self.skipped
+    }
+
+    pub fn contains(&self, id: &str, at: i64) -> bool {
+        self.fired.contains(&(id.to_string(), at))
+    }
+
+    /// Marks `id` as fired for the instant `at` and appends it to the file.
+    pub fn record(&mut self, id: &str, at: i64) -> Result<(), String> {
+        if !self.fired.insert((id.to_string(), at)) {
+            return Ok(());
+        }
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| format!("failed to open state: {}", e))?;
+        // Never append to the remains of a torn line
+        let lead = if self.torn { "\n" } else { "" };
+        writeln!(file, "{}{} {}", lead, at, id)
+            .and_then(|_| file.sync_data())
+            .map_err(|e| format!("failed to write state: {}", e))?;
+        self.torn = false;
+        Ok(())
+    }
+}
@@ -0,0 +1,629 @@
+use std::fmt;
+use std::io;
+use std::process::{Child, Command};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::parser::format_duration;
+
+/// How often a running job's memory high-water mark is sampled.
+const SAMPLE_EVERY: Duration = Duration::from_millis(100);
+
+/// SIGINT and SIGTERM received since `catch_shutdown_signals`.
+static SHUTDOWN_REQUESTS: AtomicUsize = AtomicUsize::new(0);
+
+/// Counts SIGINT and SIGTERM instead of dying on them, so croner can stop
+/// its jobs before exiting. Jobs run in process groups of their own and
+/// never see the terminal's Ctrl-C themselves.
+pub fn catch_shutdown_signals() {
+    sys::catch_signals(on_shutdown_signal);
+}
+
+/// How many times croner has been asked to shut down.
+pub fn shutdown_requests() -> usize {
+    SHUTDOWN_REQUESTS.load(Ordering::SeqCst)
+}
+
+extern "C" fn on_shutdown_signal(_signal: std::os::raw::c_int) {
+    SHUTDOWN_REQUESTS.fetch_add(1, Ordering::SeqCst);
+}
+
+/// How a job's process ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RunStatus {
+    /// Exit code, if the process exited on its own.
+    pub code: Option<i32>,
+    /// Signal that terminated the process (Unix only).
+    pub signal: Option<i32>,
+    /// Wall time from spawn to exit.
+    pub elapsed: Duration,
+    /// Peak resident set size in bytes, where the platform reports it.
+    pub max_rss: Option<u64>,
+    /// Set when the run was stopped for exceeding its timeout.
+    pub timed_out: bool,
+    /// Set when the run was stopped through its `Stopper`.
+    pub stopped: bool,
+}
+
+impl RunStatus {
+    pub fn success(&self) -> bool {
+        self.code == Some(0)
+    }
+}
+
+/// `exited 2 after 4.3s (peak RSS 18.2 MiB)`, prefixed with `timed out, `
+/// or `stopped, ` when the run had to be stopped.
+impl fmt::Display for RunStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.timed_out {
+            f.write_str("timed out, ")?;
+        } else if self.stopped {
+            f.write_str("stopped, ")?;
+        }
+        match (self.code, self.signal) {
+            (Some(code), _) => write!(f, "exited {}", code)?,
+            (None, Some(signal)) => write!(f, "killed by {}", signal_name(signal))?,
+            (None, None) => f.write_str("ended")?,
+        }
+        write!(f, " after {}", format_elapsed(self.elapsed))?;
+        if let Some(rss) = self.max_rss {
+            write!(f, " (peak RSS {})", format_bytes(rss))?;
+        }
+        Ok(())
+    }
+}
+
+/// A running job process.
+pub struct Process {
+    pub child: Child,
+    started: Instant,
+    group: sys::Group,
+    events: Sender<Event>,
+    watch: Receiver<Event>,
+}
+
+/// What the watchdog of a run hears about.
+enum Event {
+    /// `reaped` once the group id may already belong to someone else.
+    Exited {
+        reaped: bool,
+    },
+    Stop,
+    Kill,
+}
+
+/// Why the watchdog stopped a run.
+enum Reason {
+    Timeout,
+    Stopped,
+}
+
+/// Stops a run the same way its timeout would. Does nothing once the run
+/// has ended.
+#[derive(Clone)]
+pub struct Stopper(Sender<Event>);
+
+impl Stopper {
+    pub fn stop(&self) {
+        let _ = self.0.send(Event::Stop);
+    }
+
+    /// Kills the run without waiting out its grace period.
+    pub fn kill(&self) {
+        let _ = self.0.send(Event::Kill);
+    }
+}
+
+/// Starts `cmd` in a process group of its own (a job object on Windows), so
+/// everything it spawns can be stopped along with it.
+pub fn spawn(cmd: &mut Command) -> io::Result<Process> {
+    sys::isolate(cmd);
+    let started = Instant::now();
+    let mut child = cmd.spawn()?;
+    let group = match sys::Group::new(&child) {
+        Ok(group) => group,
+        Err(e) => {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(e);
+        }
+    };
+    let (events, watch) = mpsc::channel();
+    Ok(Process {
+        child,
+        started,
+        group,
+        events,
+        watch,
+    })
+}
+
+impl Process {
+    pub fn stopper(&self) -> Stopper {
+        Stopper(self.events.clone())
+    }
+
+    /// Blocks until the process exits and reaps it. Once `timeout` has
+    /// passed, or a `Stopper` asks, its group is sent SIGTERM, then SIGKILL
+    /// `grace` later, or the job object is terminated on Windows.
+    pub fn wait(self, timeout: Option<Duration>, grace: Duration) -> io::Result<RunStatus> {
+        let Process {
+            mut child,
+            started,
+            group,
+            events,
+            watch,
+        } = self;
+
+        let pid = child.id();
+        let watchdog = thread::spawn(move || {
+            // Right after exec the high-water mark says nothing yet
+            let mut peak = None;
+            let mut next = |deadline| next_event(&watch, deadline, pid, &mut peak);
+            let event = next(timeout.and_then(|t| Instant::now().checked_add(t)));
+            let grace = match event {
+                Ok(Event::Kill) => Duration::ZERO,
+                _ => grace,
+            };
+            let reason = match event {
+                Err(RecvTimeoutError::Timeout) => Reason::Timeout,
+                Ok(Event::Stop | Event::Kill) => Reason::Stopped,
+                Ok(Event::Exited { .. }) | Err(RecvTimeoutError::Disconnected) => {
+                    return (None, peak)
+                }
+            };
+            group.terminate();
+            // Whatever is left of the group once the grace period ends or
+            // the process exits is killed outright. A grace period too long
+            // to have a deadline waits for the exit alone.
+            let deadline = Instant::now().checked_add(grace);
+            loop {
+                match next(deadline) {
+                    Ok(Event::Stop) => continue,
+                    Ok(Event::Exited { reaped: true }) => return (Some(reason), peak),
+                    _ => break,
+                }
+            }
+            group.kill();
+            (Some(reason), peak)
+        });
+
+        // Leave the exited child unreaped until the watchdog is done, so the
+        // group it signals can't have been handed to another process
+        let (exit, (reason, peak)) = if sys::wait_exited(&mut child).is_ok() {
+            let _ = events.send(Event::Exited { reaped: false });
+            let watched = watchdog.join().unwrap_or_default();
+            (sys::wait(&mut child), watched)
+        } else {
+            let exit = sys::wait(&mut child);
+            let _ = events.send(Event::Exited { reaped: true });
+            (exit, watchdog.join().unwrap_or_default())
+        };
+
+        let (code, signal, max_rss) = exit?;
+        Ok(RunStatus {
+            code,
+            signal,
+            elapsed: started.elapsed(),
+            max_rss: own_peak(max_rss, peak),
+            timed_out: matches!(reason, Some(Reason::Timeout)),
+            stopped: matches!(reason, Some(Reason::Stopped)),
+        })
+    }
+}
+
+/// Waits for the next event until `deadline`, or for good without one,
+/// sampling the run's memory high-water mark into `peak` meanwhile.
+fn next_event(
+    watch: &Receiver<Event>,
+    deadline: Option<Instant>,
+    pid: u32,
+    peak: &mut Option<u64>,
+) -> Result<Event, RecvTimeoutError> {
+    loop {
+        let left = deadline.map(|d| d.saturating_duration_since(Instant::now()));
+        let event = match (left, sys::SAMPLES_RSS) {
+            (Some(left), true) => watch.recv_timeout(left.min(SAMPLE_EVERY)),
+            (Some(left), false) => watch.recv_timeout(left),
+            (None, true) => watch.recv_timeout(SAMPLE_EVERY),
+            (None, false) => watch.recv().map_err(|_| RecvTimeoutError::Disconnected),
+        };
+        match event {
+            Err(RecvTimeoutError::Timeout) if deadline.map_or(true, |d| Instant::now() < d) => {
+                *peak = (*peak).max(sys::peak_rss(pid));
+            }
+            event => return event,
+        }
+    }
+}
+
+/// On Linux a child's `ru_maxrss` also counts the memory it shared with
+/// croner until `exec`, so a figure no larger than croner's own peak says
+/// nothing about the job. The sampled high-water mark is used instead, and
+/// a run too short to be sampled has no figure at all.
+fn own_peak(reported: Option<u64>, sampled: Option<u64>) -> Option<u64> {
+    match (reported, sys::peak_rss(std::process::id())) {
+        (Some(reported), Some(ours)) if reported <= ours => sampled,
+        _ => reported,
+    }
+}
+
+/// Tenths of a second under a minute, whole units above.
+fn format_elapsed(d: Duration) -> String {
+    if d < Duration::from_secs(60) {
+        format!("{:.1}s", d.as_secs_f64())
+    } else {
+        format_duration(d.as_secs())
+    }
+}
+
+fn format_bytes(n: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+    let mut value = n as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", n)
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+fn signal_name(signal: i32) -> String {
+    let name = match signal {
+        1 => "SIGHUP",
+        2 => "SIGINT",
+        3 => "SIGQUIT",
+        4 => "SIGILL",
+        6 => "SIGABRT",
+        8 => "SIGFPE",
+        9 => "SIGKILL",
+        11 => "SIGSEGV",
+        13 => "SIGPIPE",
+        14 => "SIGALRM",
+        15 => "SIGTERM",
+        _ => return format!("signal {}", signal),
+    };
+    name.to_string()
+}
+
+/// Exit code, terminating signal and peak RSS in bytes.
+type Exit = (Option<i32>, Option<i32>, Option<u64>);
+
+#[cfg(unix)]
+mod sys {
+    use std::io;
+    use std::os::raw::{c_int, c_long, c_uint};
+    use std::os::unix::process::CommandExt;
+    use std::process::{Child, Command};
+
+    use super::Exit;
+
+    const SIGINT: c_int = 2;
+    const SIGKILL: c_int = 9;
+    const SIGTERM: c_int = 15;
+
+    #[repr(C)]
+    struct Timeval {
+        tv_sec: c_long,
+        tv_usec: c_long,
+    }
+
+    #[repr(C)]
+    struct Rusage {
+        ru_utime: Timeval,
+        ru_stime: Timeval,
+        ru_maxrss: c_long,
+        rest: [c_long; 13],
+    }
+
+    extern "C" {
+        fn wait4(pid: c_int, status: *mut c_int, options: c_int, rusage: *mut Rusage) -> c_int;
+        fn kill(pid: c_int, signal: c_int) -> c_int;
+        fn signal(signal: c_int, handler: extern "C" fn(c_int)) -> usize;
+        fn waitid(idtype: c_int, id: c_uint, info: *mut [u64; 16], options: c_int) -> c_int;
+    }
+
+    const P_PID: c_int = 1;
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    const WEXITED_NOWAIT: Option<c_int> = Some(0x4 | 0x0100_0000);
+    #[cfg(any(target_os = "macos", target_os = "ios"))]
+    const WEXITED_NOWAIT: Option<c_int> = Some(0x4 | 0x20);
+    #[cfg(not(any(
+        target_os = "linux",
+        target_os = "android",
+        target_os = "macos",
+        target_os = "ios"
+    )))]
+    const WEXITED_NOWAIT: Option<c_int> = None;
+
+    /// Blocks until the child has exited without reaping it, so its pid
+    /// and process group id stay reserved.
+    pub fn wait_exited(child: &mut Child) -> io::Result<()> {
+        let options = WEXITED_NOWAIT.ok_or(io::ErrorKind::Unsupported)?;
+        // Large enough for any platform's siginfo_t, which isn't read
+        let mut info = [0u64; 16];
+        loop {
+            // SAFETY: `info` outlives the call and is at least siginfo_t sized
+            if unsafe { waitid(P_PID, child.id() as c_uint, &mut info, options) } == 0 {
+                return Ok(());
+            }
+            let e = io::Error::last_os_error();
+            if e.kind() != io::ErrorKind::Interrupted {
+                return Err(e);
+            }
+        }
+    }
+
+    /// Whether `peak_rss` can watch a running process.
+    pub const SAMPLES_RSS: bool = cfg!(any(target_os = "linux", target_os = "android"));
+
+    /// The process's memory high-water mark so far, from `VmHWM` in
+    /// `/proc/<pid>/status`. `None` without procfs or once it has exited.
+    pub fn peak_rss(pid: u32) -> Option<u64> {
+        if !SAMPLES_RSS {
+            return None;
+        }
+        let status = std::fs::read_to_string(format!("/proc/{}/status", pid)).ok()?;
+        let line = status.lines().find_map(|l| l.strip_prefix("VmHWM:"))?;
+        let kib: u64 = line.trim().strip_suffix("kB")?.trim().parse().ok()?;
+        Some(kib * 1024)
+    }
+
+    pub fn catch_signals(handler: extern "C" fn(c_int)) {
+        // SAFETY: the handler only touches an atomic
+        unsafe {
+            signal(SIGINT, handler);
+            signal(SIGTERM, handler);
+        }
+    }
+
+    pub fn isolate(cmd: &mut Command) {
+        cmd.process_group(0);
+    }
+
+    /// The child's process group, whose id is the child's pid.
+    #[derive(Clone)]
+    pub struct Group(c_int);
+
+    impl Group {
+        pub fn new(child: &Child) -> io::Result<Self> {
+            Ok(Self(child.id() as c_int))
+        }
+
+        pub fn terminate(&self) {
+            self.signal(SIGTERM);
+        }
+
+        pub fn kill(&self) {
+            self.signal(SIGKILL);
+        }
+
+        fn signal(&self, signal: c_int) {
+            // SAFETY: plain syscall; fails with ESRCH once the group is gone
+            unsafe { kill(-self.0, signal) };
+        }
+    }
+
+    /// `wait4` rather than `Child::wait` so the kernel hands back the
+    /// child's resource usage along with its status.
+    pub fn wait(child: &mut Child) -> io::Result<Exit> {
+        let mut status: c_int = 0;
+        // SAFETY: all-zero is a valid `Rusage`, it is plain integers
+        let mut usage: Rusage = unsafe { std::mem::zeroed() };
+        loop {
+            // SAFETY: both pointers are to live locals of the declared types
+            let pid = unsafe { wait4(child.id() as c_int, &mut status, 0, &mut usage) };
+            if pid != -1 {
+                break;
+            }
+            let e = io::Error::last_os_error();
+            if e.kind() != io::ErrorKind::Interrupted {
+                return Err(e);
+            }
+        }
+
+        let signal = status & 0x7f;
+        let (code, signal) = if signal == 0 {
+            (Some((status >> 8) & 0xff), None)
+        } else {
+            (None, Some(signal))
+        };
+        // Bytes on Apple platforms, kilobytes everywhere else
+        let scale = if cfg!(any(target_os = "macos", target_os = "ios")) {
+            1
+        } else {
+            1024
+        };
+        let max_rss = u64::try_from(usage.ru_maxrss)
+            .ok()
+            .filter(|&n| n > 0)
+            .map(|n| n * scale);
+        Ok((code, signal, max_rss))
+    }
+}
+
+#[cfg(windows)]
+mod sys {
+    use std::ffi::c_void;
+    use std::io;
+    use std::mem::size_of;
+    use std::os::windows::io::AsRawHandle;
+    use std::os::windows::process::CommandExt;
+    use std::process::{Child, Command};
+    use std::ptr;
+    use std::sync::Arc;
+
+    use super::Exit;
+
+    #[repr(C)]
+    struct ProcessMemoryCounters {
+        cb: u32,
+        page_fault_count: u32,
+        peak_working_set_size: usize,
+        rest: [usize; 7],
+    }
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn K32GetProcessMemoryInfo(
+            process: *mut c_void,
+            counters: *mut ProcessMemoryCounters,
+            cb: u32,
+        ) -> i32;
+        fn CreateJobObjectW(attributes: *mut c_void, name: *const u16) -> *mut c_void;
+        fn AssignProcessToJobObject(job: *mut c_void, process: *mut c_void) -> i32;
+        fn TerminateJobObject(job: *mut c_void, exit_code: u32) -> i32;
+        fn CloseHandle(handle: *mut c_void) -> i32;
+    }
+
+    #[link(name = "ntdll")]
+    extern "system" {
+        fn NtResumeProcess(process: *mut c_void) -> i32;
+    }
+
+    const CREATE_SUSPENDED: u32 = 0x0000_0004;
+
+    extern "C" {
+        fn signal(signal: i32, handler: extern "C" fn(i32)) -> usize;
+    }
+
+    pub fn catch_signals(handler: extern "C" fn(i32)) {
+        const SIGINT: i32 = 2;
+        const SIGTERM: i32 = 15;
+        // SAFETY: the handler only touches an atomic
+        unsafe {
+            signal(SIGINT, handler);
+            signal(SIGTERM, handler);
+        }
+    }
+
+    /// The child starts suspended so it can join its job object before it
+    /// gets a chance to spawn anything.
+    pub fn isolate(cmd: &mut Command) {
+        cmd.creation_flags(CREATE_SUSPENDED);
+    }
+
+    pub const SAMPLES_RSS: bool = false;
+
+    pub fn peak_rss(_pid: u32) -> Option<u64> {
+        None
+    }
+
+    /// The job object keeps its own handle, so nothing can be reused early.
+    pub fn wait_exited(child: &mut Child) -> io::Result<()> {
+        child.wait().map(drop)
+    }
+
+    /// A job object holding the child and everything it starts.
+    #[derive(Clone)]
+    pub struct Group(Arc<Job>);
+
+    struct Job(*mut c_void);
+
+    // SAFETY: a job handle may be used and closed from any thread
+    unsafe impl Send for Job {}
+    unsafe impl Sync for Job {}
+
+    impl Drop for Job {
+        fn drop(&mut self) {
+            // SAFETY: the handle came from CreateJobObjectW and is closed once
+            unsafe { CloseHandle(self.0) };
+        }
+    }
+
+    impl Group {
+        pub fn new(child: &Child) -> io::Result<Self> {
+            // SAFETY: null attributes and name ask for a default, unnamed job
+            let handle = unsafe { CreateJobObjectW(ptr::null_mut(), ptr::null()) };
+            if handle.is_null() {
+                return Err(io::Error::last_os_error());
+            }
+            let job = Job(handle);
+            // SAFETY: both handles are open for the duration of the call
+            if unsafe { AssignProcessToJobObject(job.0, child.as_raw_handle()) } == 0 {
+                return Err(io::Error::last_os_error());
+            }
+            // SAFETY: the process handle is open and the process suspended
+            let status = unsafe { NtResumeProcess(child.as_raw_handle()) };
+            if status < 0 {
+                return Err(io::Error::other(format!(
+                    "failed to resume process: NTSTATUS {:#x}",
+                    status
+                )));
+            }
+            Ok(Self(Arc::new(job)))
+        }
+
+        /// Windows has no polite stop signal; the whole job ends at once.
+        pub fn terminate(&self) {
+            // SAFETY: the job handle is open while `self` is alive
+            unsafe { TerminateJobObject((self.0).0, 1) };
+        }
+
+        pub fn kill(&self) {
+            self.terminate();
+        }
+    }
+
+    pub fn wait(child: &mut Child) -> io::Result<Exit> {
+        let status = child.wait()?;
+        let mut counters = ProcessMemoryCounters {
+            cb: size_of::<ProcessMemoryCounters>() as u32,
+            page_fault_count: 0,
+            peak_working_set_size: 0,
+            rest: [0; 7],
+        };
+        // SAFETY: the handle stays open until `child` is dropped
+        let ok = unsafe {
+            K32GetProcessMemoryInfo(child.as_raw_handle(), &mut counters, counters.cb) != 0
+        };
+        let max_rss = ok.then_some(counters.peak_working_set_size as u64);
+        Ok((status.code(), None, max_rss))
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+mod sys {
+    use std::io;
+    use std::process::{Child, Command};
+
+    use super::Exit;
+
+    pub fn catch_signals(_handler: extern "C" fn(std::os::raw::c_int)) {}
+
+    pub fn isolate(_cmd: &mut Command) {}
+
+    pub const SAMPLES_RSS: bool = false;
+
+    pub fn peak_rss(_pid: u32) -> Option<u64> {
+        None
+    }
+
+    pub fn wait_exited(_child: &mut Child) -> io::Result<()> {
+        Err(io::ErrorKind::Unsupported.into())
+    }
+
+    /// Without process groups there is nothing to signal.
+    #[derive(Clone)]
+    pub struct Group;
+
+    impl Group {
+        pub fn new(_child: &Child) -> io::Result<Self> {
+            Ok(Self)
+        }
+
+        pub fn terminate(&self) {}
+
+        pub fn kill(&self) {}
+    }
+
+    pub fn wait(child: &mut Child) -> io::Result<Exit> {
+        Ok((child.wait()?.code(), None, None))
+    }
+}
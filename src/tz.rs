@@ -0,0 +1,673 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::datetime::{days_from_civil, days_in_month, is_leap_year, weekday_from_days, DateTime};
+
+/// Directories searched for compiled tz database files, in order.
+/// `TZDIR` takes precedence when set.
+const ZONEINFO_DIRS: [&str; 4] = [
+    "/usr/share/zoneinfo",
+    "/usr/lib/zoneinfo",
+    "/usr/share/lib/zoneinfo",
+    "/etc/zoneinfo",
+];
+
+/// A time zone: historical transitions from a TZif file plus an optional
+/// POSIX rule that extends them into the future.
+#[derive(Debug, Clone)]
+pub struct TimeZone {
+    name: String,
+    /// UTC instants at which `offsets[i]` takes effect, ascending.
+    transitions: Vec<i64>,
+    offsets: Vec<i32>,
+    /// Offset in effect before the first transition.
+    initial_offset: i32,
+    /// Rule applied after the last transition (TZif footer).
+    rule: Option<PosixRule>,
+}
+
+impl TimeZone {
+    pub fn utc() -> Self {
+        Self::fixed("UTC", 0)
+    }
+
+    pub fn fixed(name: &str, offset: i32) -> Self {
+        Self {
+            name: name.to_string(),
+            transitions: Vec::new(),
+            offsets: Vec::new(),
+            initial_offset: offset,
+            rule: None,
+        }
+    }
+
+    /// Resolves a zone by name: `UTC`, `local`, an IANA name such as
+    /// `Europe/Zagreb` (looked up in the system tz database), or a raw POSIX
+    /// TZ string like `CET-1CEST,M3.5.0,M10.5.0/3`.
+    pub fn named(name: &str) -> Result<Self, String> {
+        let name = name.trim();
+        match name {
+            "" => Err("empty time zone".into()),
+            "UTC" | "Etc/UTC" | "Z" => Ok(Self::utc()),
+            "local" => Ok(Self::local()),
+            _ => {
+                if let Some(path) = find_zoneinfo(name) {
+                    let bytes = fs::read(&path)
+                        .map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+                    return Self::from_tzif(name, &bytes);
+                }
+                Self::from_posix(name).map_err(|_| match zoneinfo_dirs().next() {
+                    Some(_) => format!("unknown time zone '{}'", name),
+                    None => format!(
+                        "unknown time zone '{}': no tz database found, set TZDIR",
+                        name
+                    ),
+                })
+            }
+        }
+    }
+
+    /// The system zone: `TZ` if set, then `/etc/localtime` (the zone
+    /// Windows is set to, on Windows), otherwise UTC.
+    pub fn local() -> Self {
+        if let Ok(tz) = env::var("TZ") {
+            let tz = tz.strip_prefix(':').unwrap_or(&tz);
+            if !tz.is_empty() && tz != "local" {
+                if Path::new(tz).is_absolute() {
+                    if let Ok(bytes) = fs::read(tz) {
+                        if let Ok(zone) = Self::from_tzif(tz, &bytes) {
+                            return zone;
+                        }
+                    }
+                } else if let Ok(zone) = Self::named(tz) {
+                    return zone;
+                }
+            }
+        }
+
+        #[cfg(windows)]
+        {
+            if let Some(zone) = windows::local() {
+                return zone;
+            }
+        }
+
+        let localtime = Path::new("/etc/localtime");
+        if let Ok(bytes) = fs::read(localtime) {
+            let name = fs::read_link(localtime)
+                .ok()
+                .and_then(|target| {
+                    let target = target.to_string_lossy().into_owned();
+                    target
+                        .split_once("zoneinfo/")
+                        .map(|(_, name)| name.to_string())
+                })
+                .unwrap_or_else(|| "local".to_string());
+            if let Ok(zone) = Self::from_tzif(&name, &bytes) {
+                return zone;
+            }
+        }
+
+        Self::utc()
+    }
+
+    /// Parses a compiled TZif (RFC 8536) file. Version 2+ files use the
+    /// 64-bit block and footer rule; version 1 files fall back to 32-bit data.
+    pub fn from_tzif(name: &str, bytes: &[u8]) -> Result<Self, String> {
+        let bad = || format!("invalid TZif data for '{}'", name);
+
+        let header = TzifHeader::read(bytes).ok_or_else(bad)?;
+        let (header, body, time_size) = if header.version >= b'2' {
+            let skip = 44 + header.block_len(4);
+            let second = TzifHeader::read(bytes.get(skip..).ok_or_else(bad)?).ok_or_else(bad)?;
+            (second, &bytes[skip + 44..], 8)
+        } else {
+            (header, &bytes[44..], 4)
+        };
+
+        if body.len() < header.block_len(time_size) {
+            return Err(bad());
+        }
+
+        let mut pos = 0usize;
+        let mut transitions = Vec::with_capacity(header.timecnt);
+        for _ in 0..header.timecnt {
+            let t = if time_size == 8 {
+                i64::from_be_bytes(body[pos..pos + 8].try_into().unwrap())
+            } else {
+                i32::from_be_bytes(body[pos..pos + 4].try_into().unwrap()) as i64
+            };
+            transitions.push(t);
+            pos += time_size;
+        }
+
+        let indices = &body[pos..pos + header.timecnt];
+        pos += header.timecnt;
+
+        let mut types = Vec::with_capacity(header.typecnt);
+        for _ in 0..header.typecnt {
+            let utoff = i32::from_be_bytes(body[pos..pos + 4].try_into().unwrap());
+            let is_dst = body[pos + 4] != 0;
+            types.push((utoff, is_dst));
+            pos += 6;
+        }
+        if types.is_empty() {
+            return Err(bad());
+        }
+
+        let mut offsets = Vec::with_capacity(indices.len());
+        for &idx in indices {
+            let (utoff, _) = *types.get(idx as usize).ok_or_else(bad)?;
+            offsets.push(utoff);
+        }
+
+        // RFC 8536: local time before the first transition uses type 0.
+        let initial_offset = types[0].0;
+
+        let rule = if time_size == 8 {
+            let footer = &body[header.block_len(time_size)..];
+            parse_footer(footer)
+        } else {
+            None
+        };
+
+        Ok(Self {
+            name: name.to_string(),
+            transitions,
+            offsets,
+            initial_offset,
+            rule,
+        })
+    }
+
+    /// Builds a zone purely from a POSIX TZ rule string.
+    pub fn from_posix(spec: &str) -> Result<Self, String> {
+        let rule = PosixRule::parse(spec)?;
+        Ok(Self {
+            name: spec.to_string(),
+            transitions: Vec::new(),
+            offsets: Vec::new(),
+            initial_offset: rule.std_offset,
+            rule: Some(rule),
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Seconds east of UTC in effect at the given UTC instant.
+    pub fn offset_at(&self, utc: i64) -> i32 {
+        let idx = self.transitions.partition_point(|&t| t <= utc);
+        if idx == self.transitions.len() {
+            if let Some(rule) = &self.rule {
+                return rule.offset_at(utc);
+            }
+        }
+        if idx == 0 {
+            self.initial_offset
+        } else {
+            self.offsets[idx - 1]
+        }
+    }
+
+    /// First instant strictly after `utc` at which the offset changes.
+    pub fn next_transition(&self, utc: i64) -> Option<i64> {
+        let mut idx = self.transitions.partition_point(|&t| t <= utc);
+        let mut current = self.offset_at(utc);
+        while idx < self.transitions.len() {
+            if self.offsets[idx] != current {
+                return Some(self.transitions[idx]);
+            }
+            current = self.offsets[idx];
+            idx += 1;
+        }
+        let from = utc.max(self.transitions.last().copied().unwrap_or(i64::MIN));
+        self.rule.as_ref().and_then(|r| r.next_transition(from))
+    }
+
+    /// Last instant at or before `utc` at which the offset changed.
+    pub fn prev_transition(&self, utc: i64) -> Option<i64> {
+        let last = self.transitions.last().copied();
+        if let Some(rule) = &self.rule {
+            if last.map_or(true, |l| utc > l) {
+                if let Some(t) = rule.prev_transition(utc) {
+                    if last.map_or(true, |l| t > l) {
+                        return Some(t);
+                    }
+                }
+            }
+        }
+
+        let mut idx = self.transitions.partition_point(|&t| t <= utc);
+        while idx > 0 {
+            let before = if idx >= 2 {
+                self.offsets[idx - 2]
+            } else {
+                self.initial_offset
+            };
+            if self.offsets[idx - 1] != before {
+                return Some(self.transitions[idx - 1]);
+            }
+            idx -= 1;
+        }
+        None
+    }
+
+    /// Wall-clock reading of a UTC instant in this zone.
+    pub fn to_local(&self, utc: i64) -> DateTime {
+        DateTime::from_unix(utc + self.offset_at(utc) as i64)
+    }
+
+    /// UTC instant of a wall-clock reading. An ambiguous reading resolves to
+    /// the earlier instant; one inside a gap is pushed forward past it.
+    pub fn to_utc(&self, local: &DateTime) -> i64 {
+        let l = local.to_unix();
+        let before = self.offset_at(l - 86_400) as i64;
+        let after = self.offset_at(l + 86_400) as i64;
+
+        let mut candidates = [l - before, l - after];
+        candidates.sort_unstable();
+        candidates
+            .into_iter()
+            .find(|&utc| self.offset_at(utc) as i64 == l - utc)
+            .unwrap_or(l - before)
+    }
+}
+
+fn find_zoneinfo(name: &str) -> Option<PathBuf> {
+    // Only plain relative names like `Europe/Zagreb`; never escape the tz dir.
+    if name.starts_with('/') || name.split('/').any(|c| c == ".." || c.is_empty()) {
+        return None;
+    }
+
+    zoneinfo_dirs()
+        .map(|dir| dir.join(name))
+        .find(|p| p.is_file())
+}
+
+/// The tz database directories that exist, `TZDIR` first.
+fn zoneinfo_dirs() -> impl Iterator<Item = PathBuf> {
+    env::var_os("TZDIR")
+        .map(PathBuf::from)
+        .into_iter()
+        .chain(ZONEINFO_DIRS.iter().map(PathBuf::from))
+        .filter(|dir| dir.is_dir())
+}
+
+struct TzifHeader {
+    version: u8,
+    isutcnt: usize,
+    isstdcnt: usize,
+    leapcnt: usize,
+    timecnt: usize,
+    typecnt: usize,
+    charcnt: usize,
+}
+
+impl TzifHeader {
+    fn read(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() < 44 || &bytes[0..4] != b"TZif" {
+            return None;
+        }
+        let count = |i: usize| {
+            let at = 20 + i * 4;
+            u32::from_be_bytes(bytes[at..at + 4].try_into().unwrap()) as usize
+        };
+        Some(Self {
+            version: bytes[4],
+            isutcnt: count(0),
+            isstdcnt: count(1),
+            leapcnt: count(2),
+            timecnt: count(3),
+            typecnt: count(4),
+            charcnt: count(5),
+        })
+    }
+
+    /// Length of the data block following this header.
+    fn block_len(&self, time_size: usize) -> usize {
+        self.timecnt * time_size
+            + self.timecnt
+            + self.typecnt * 6
+            + self.charcnt
+            + self.leapcnt * (time_size + 4)
+            + self.isstdcnt
+            + self.isutcnt
+    }
+}
+
+fn parse_footer(footer: &[u8]) -> Option<PosixRule> {
+    let text = std::str::from_utf8(footer).ok()?;
+    let spec = text.strip_prefix('\n')?.split('\n').next()?;
+    if spec.is_empty() {
+        return None;
+    }
+    PosixRule::parse(spec).ok()
+}
+
+/// POSIX TZ rule, e.g. `CET-1CEST,M3.5.0,M10.5.0/3`.
+#[derive(Debug, Clone)]
+struct PosixRule {
+    std_offset: i32,
+    dst: Option<DstRule>,
+}
+
+#[derive(Debug, Clone)]
+struct DstRule {
+    offset: i32,
+    start: RuleDate,
+    start_time: i32,
+    end: RuleDate,
+    end_time: i32,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum RuleDate {
+    /// `Jn`: day 1..365, February 29 never counted.
+    Julian1(u16),
+    /// `n`: day 0..365, February 29 counted in leap years.
+    Julian0(u16),
+    /// `Mm.w.d`: weekday `d` of week `w` (5 = last) of month `m`.
+    MonthWeekDay(u8, u8, u8),
+}
+
+impl PosixRule {
+    fn parse(spec: &str) -> Result<Self, String> {
+        let err = || format!("invalid POSIX TZ rule '{}'", spec);
+        let mut s = spec.as_bytes();
+
+        skip_name(&mut s).ok_or_else(err)?;
+        // POSIX offsets count hours *west* of Greenwich.
+        let std_offset = -parse_hms(&mut s, MAX_OFFSET).ok_or_else(err)?;
+
+        if s.is_empty() {
+            return Ok(Self {
+                std_offset,
+                dst: None,
+            });
+        }
+
+        skip_name(&mut s).ok_or_else(err)?;
+        let dst_offset = if !s.is_empty() && s[0] != b',' {
+            -parse_hms(&mut s, MAX_OFFSET).ok_or_else(err)?
+        } else {
+            std_offset + 3600
+        };
+
+        // Rules are required in practice; default to the US rules otherwise.
+        let (start, start_time, end, end_time) = if s.is_empty() {
+            (
+                RuleDate::MonthWeekDay(3, 2, 0),
+                7200,
+                RuleDate::MonthWeekDay(11, 1, 0),
+                7200,
+            )
+        } else {
+            s = s.strip_prefix(b",").ok_or_else(err)?;
+            let (start, start_time) = parse_rule_date(&mut s).ok_or_else(err)?;
+            s = s.strip_prefix(b",").ok_or_else(err)?;
+            let (end, end_time) = parse_rule_date(&mut s).ok_or_else(err)?;
+            (start, start_time, end, end_time)
+        };
+
+        if !s.is_empty() {
+            return Err(err());
+        }
+
+        Ok(Self {
+            std_offset,
+            dst: Some(DstRule {
+                offset: dst_offset,
+                start,
+                start_time,
+                end,
+                end_time,
+            }),
+        })
+    }
+
+    /// DST start and end instants (UTC) for a calendar year.
+    fn transitions_in(&self, year: i32) -> Option<(i64, i64)> {
+        let dst = self.dst.as_ref()?;
+        // Start is expressed in standard time, end in daylight time.
+        let start =
+            dst.start.day_in(year) * 86_400 + dst.start_time as i64 - self.std_offset as i64;
+        let end = dst.end.day_in(year) * 86_400 + dst.end_time as i64 - dst.offset as i64;
+        Some((start, end))
+    }
+
+    fn offset_at(&self, utc: i64) -> i32 {
+        let Some(dst) = &self.dst else {
+            return self.std_offset;
+        };
+        let year = DateTime::from_unix(utc + self.std_offset as i64).year;
+        let (start, end) = self.transitions_in(year).unwrap();
+        let in_dst = if start < end {
+            utc >= start && utc < end
+        } else {
+            // Southern hemisphere: DST spans the new year.
+            !(utc >= end && utc < start)
+        };
+        if in_dst {
+            dst.offset
+        } else {
+            self.std_offset
+        }
+    }
+
+    fn next_transition(&self, utc: i64) -> Option<i64> {
+        let year = DateTime::from_unix(utc).year;
+        (year - 1..=year + 1)
+            .filter_map(|y| self.transitions_in(y))
+            .flat_map(|(a, b)| [a, b])
+            .filter(|&t| t > utc)
+            .min()
+    }
+
+    fn prev_transition(&self, utc: i64) -> Option<i64> {
+        let year = DateTime::from_unix(utc).year;
+        (year - 1..=year + 1)
+            .filter_map(|y| self.transitions_in(y))
+            .flat_map(|(a, b)| [a, b])
+            .filter(|&t| t <= utc)
+            .max()
+    }
+}
+
+impl RuleDate {
+    /// Days since the Unix epoch of this rule's date in `year`.
+    fn day_in(&self, year: i32) -> i64 {
+        let jan1 = days_from_civil(year, 1, 1);
+        match *self {
+            RuleDate::Julian1(n) => {
+                let leap_shift = if is_leap_year(year) && n >= 60 { 1 } else { 0 };
+                jan1 + n as i64 - 1 + leap_shift
+            }
+            RuleDate::Julian0(n) => jan1 + n as i64,
+            RuleDate::MonthWeekDay(month, week, weekday) => {
+                let first = days_from_civil(year, month, 1);
+                let first_wd = weekday_from_days(first);
+                let mut day =
+                    1 + (7 + weekday as i64 - first_wd as i64) % 7 + (week as i64 - 1) * 7;
+                let dim = days_in_month(year, month) as i64;
+                while day > dim {
+                    day -= 7;
+                }
+                first + day - 1
+            }
+        }
+    }
+}
+
+fn skip_name(s: &mut &[u8]) -> Option<()> {
+    if s.first() == Some(&b'<') {
+        let end = s.iter().position(|&b| b == b'>')?;
+        *s = &s[end + 1..];
+        return Some(());
+    }
+    let len = s.iter().take_while(|b| b.is_ascii_alphabetic()).count();
+    if len < 3 {
+        return None;
+    }
+    *s = &s[len..];
+    Some(())
+}
+
+/// POSIX caps UTC offsets at `24:59:59`.
+const MAX_OFFSET: i32 = 24 * 3600 + 59 * 60 + 59;
+/// Transition times may reach `167:59:59` (RFC 8536).
+const MAX_RULE_TIME: i32 = 167 * 3600 + 59 * 60 + 59;
+
+/// `[+-]hh[:mm[:ss]]` in seconds, `None` past `max`.
+fn parse_hms(s: &mut &[u8], max: i32) -> Option<i32> {
+    let mut sign = 1;
+    if let Some(&c) = s.first() {
+        if c == b'+' || c == b'-' {
+            if c == b'-' {
+                sign = -1;
+            }
+            *s = &s[1..];
+        }
+    }
+
+    let mut total = 0i32;
+    for (i, scale) in [3600, 60, 1].into_iter().enumerate() {
+        if i > 0 {
+            if s.first() != Some(&b':') {
+                break;
+            }
+            *s = &s[1..];
+        }
+        let n = i32::try_from(parse_num(s)?).ok()?;
+        total = n.checked_mul(scale).and_then(|v| total.checked_add(v))?;
+    }
+    (total <= max).then_some(sign * total)
+}
+
+fn parse_num(s: &mut &[u8]) -> Option<u32> {
+    let len = s.iter().take_while(|b| b.is_ascii_digit()).count();
+    if len == 0 {
+        return None;
+    }
+    let n = std::str::from_utf8(&s[..len]).ok()?.parse().ok()?;
+    *s = &s[len..];
+    Some(n)
+}
+
+fn parse_rule_date(s: &mut &[u8]) -> Option<(RuleDate, i32)> {
+    // Checked before narrowing, so `M268` is not read as December
+    let date = match s.first()? {
+        b'J' => {
+            *s = &s[1..];
+            let n = parse_num(s).filter(|n| (1..=365).contains(n))?;
+            RuleDate::Julian1(n as u16)
+        }
+        b'M' => {
+            *s = &s[1..];
+            let m = parse_num(s)?;
+            *s = s.strip_prefix(b".")?;
+            let w = parse_num(s)?;
+            *s = s.strip_prefix(b".")?;
+            let d = parse_num(s)?;
+            if !(1..=12).contains(&m) || !(1..=5).contains(&w) || d > 6 {
+                return None;
+            }
+            RuleDate::MonthWeekDay(m as u8, w as u8, d as u8)
+        }
+        _ => RuleDate::Julian0(parse_num(s).filter(|&n| n <= 365)? as u16),
+    };
+
+    let time = if s.first() == Some(&b'/') {
+        *s = &s[1..];
+        parse_hms(s, MAX_RULE_TIME)?
+    } else {
+        7200
+    };
+
+    Some((date, time))
+}
+
+#[cfg(windows)]
+mod windows {
+    use super::TimeZone;
+
+    #[repr(C)]
+    struct SystemTime {
+        year: u16,
+        month: u16,
+        day_of_week: u16,
+        day: u16,
+        hour: u16,
+        minute: u16,
+        second: u16,
+        milliseconds: u16,
+    }
+
+    #[repr(C)]
+    struct TimeZoneInformation {
+        bias: i32,
+        standard_name: [u16; 32],
+        standard_date: SystemTime,
+        standard_bias: i32,
+        daylight_name: [u16; 32],
+        daylight_date: SystemTime,
+        daylight_bias: i32,
+    }
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetTimeZoneInformation(info: *mut TimeZoneInformation) -> u32;
+    }
+
+    const TIME_ZONE_ID_INVALID: u32 = 0xFFFF_FFFF;
+
+    /// The zone Windows is set to, as a POSIX rule. Windows only hands out
+    /// the current rules, so earlier offset changes are not known.
+    pub fn local() -> Option<TimeZone> {
+        // SAFETY: all-zero is a valid `TimeZoneInformation`, it is plain integers
+        let mut info: TimeZoneInformation = unsafe { std::mem::zeroed() };
+        // SAFETY: `info` is a live local of the declared layout
+        if unsafe { GetTimeZoneInformation(&mut info) } == TIME_ZONE_ID_INVALID {
+            return None;
+        }
+
+        let std = offset(info.bias + info.standard_bias);
+        // Transitions on a fixed date (a non-zero year) have no POSIX form
+        let spec = if info.daylight_date.month == 0
+            || info.daylight_date.year != 0
+            || info.standard_date.year != 0
+        {
+            format!("<STD>{}", std)
+        } else {
+            format!(
+                "<STD>{}<DST>{},{},{}",
+                std,
+                offset(info.bias + info.daylight_bias),
+                date(&info.daylight_date),
+                date(&info.standard_date)
+            )
+        };
+        let mut zone = TimeZone::from_posix(&spec).ok()?;
+        let name = String::from_utf16_lossy(&info.standard_name);
+        let name = name.trim_end_matches('\0');
+        zone.name = if name.is_empty() { "local" } else { name }.to_string();
+        Some(zone)
+    }
+
+    /// Minutes west of UTC as a POSIX offset, e.g. `-1:00`.
+    fn offset(minutes: i32) -> String {
+        let sign = if minutes < 0 { "-" } else { "" };
+        let minutes = minutes.unsigned_abs();
+        format!("{}{}:{:02}", sign, minutes / 60, minutes % 60)
+    }
+
+    /// `Mm.w.d/h:mm:ss`. Week 5 is the month's last, as in POSIX.
+    fn date(t: &SystemTime) -> String {
+        format!(
+            "M{}.{}.{}/{}:{:02}:{:02}",
+            t.month, t.day, t.day_of_week, t.hour, t.minute, t.second
+        )
+    }
+}
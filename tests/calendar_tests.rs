@@ -0,0 +1,40 @@
+use croner::calendar::Calendar;
+use croner::datetime::days_from_civil;
+
+#[test]
+fn adds_dates_and_ranges() {
+    let mut c = Calendar::new("holidays");
+    assert!(c.is_empty());
+
+    c.add("2025-12-25").unwrap();
+    c.add(" 2025-08-01..2025-08-15 ").unwrap();
+
+    assert!(c.contains(2025, 12, 25));
+    assert!(!c.contains(2025, 12, 24));
+    assert!(c.contains(2025, 8, 1));
+    assert!(c.contains(2025, 8, 15));
+    assert!(!c.contains(2025, 8, 16));
+}
+
+#[test]
+fn rejects_bad_entries() {
+    let mut c = Calendar::new("bad");
+    assert!(c.add("2025-02-30").is_err());
+    assert!(c.add("christmas").is_err());
+    assert!(c.add("2025-12-31..2025-12-01").is_err());
+    assert!(c.is_empty());
+}
+
+#[test]
+fn next_open_day_skips_merged_ranges() {
+    let mut c = Calendar::new("shutdown");
+    c.add("2025-12-22..2025-12-24").unwrap();
+    c.add("2025-12-25..2025-12-26").unwrap(); // adjacent
+    c.add("2025-12-23..2025-12-28").unwrap(); // overlapping
+
+    let day = |d: u8| days_from_civil(2025, 12, d);
+    assert_eq!(c.next_open_day(day(21)), day(21));
+    assert_eq!(c.next_open_day(day(22)), day(29));
+    assert_eq!(c.next_open_day(day(27)), day(29));
+    assert!(c.contains_day(day(26)));
+}
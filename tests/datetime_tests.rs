@@ -0,0 +1,61 @@
+use croner::datetime::{civil_from_days, days_from_civil, days_in_month, is_leap_year, DateTime};
+
+#[test]
+fn epoch_round_trips() {
+    let dt = DateTime::from_unix(0);
+    assert_eq!(dt, DateTime::new(1970, 1, 1, 0, 0, 0));
+    assert_eq!(dt.to_unix(), 0);
+    assert_eq!(dt.weekday(), 4); // Thursday
+}
+
+#[test]
+fn known_timestamps() {
+    // 2024-02-29T12:34:56Z
+    let dt = DateTime::from_unix(1_709_210_096);
+    assert_eq!(dt, DateTime::new(2024, 2, 29, 12, 34, 56));
+    assert_eq!(dt.weekday(), 4);
+
+    // Before the epoch.
+    let dt = DateTime::from_unix(-1);
+    assert_eq!(dt, DateTime::new(1969, 12, 31, 23, 59, 59));
+}
+
+#[test]
+fn civil_days_round_trip_over_centuries() {
+    for days in (-200_000..200_000).step_by(97) {
+        let (y, m, d) = civil_from_days(days);
+        assert_eq!(days_from_civil(y, m, d), days);
+    }
+}
+
+#[test]
+fn leap_years_and_month_lengths() {
+    assert!(is_leap_year(2000));
+    assert!(is_leap_year(2024));
+    assert!(!is_leap_year(1900));
+    assert!(!is_leap_year(2100));
+
+    assert_eq!(days_in_month(2024, 2), 29);
+    assert_eq!(days_in_month(2025, 2), 28);
+    assert_eq!(days_in_month(2025, 4), 30);
+    assert_eq!(days_in_month(2025, 12), 31);
+}
+
+#[test]
+fn parses_date_and_time() {
+    assert_eq!(
+        DateTime::parse("2025-03-10 09:30"),
+        Ok(DateTime::new(2025, 3, 10, 9, 30, 0))
+    );
+    assert_eq!(
+        DateTime::parse("2024-02-29T23:59:59"),
+        Ok(DateTime::new(2024, 2, 29, 23, 59, 59))
+    );
+    for bad in ["2025-03-10", "2025-02-29 00:00", "2025-03-10 24:00", "soon"] {
+        assert!(
+            DateTime::parse(bad).is_err(),
+            "{:?} should be rejected",
+            bad
+        );
+    }
+}
@@ -0,0 +1,64 @@
+use croner::parser::CronParser;
+
+fn describe(expr: &str) -> String {
+    CronParser::new().parse_schedule(expr).unwrap().describe()
+}
+
+#[test]
+fn describes_time_fields() {
+    assert_eq!(
+        describe("*/15 9-17 * * 1-5"),
+        "every 15 minutes, between 09:00 and 17:59, Monday through Friday"
+    );
+    assert_eq!(describe("* * * * *"), "every minute");
+    assert_eq!(describe("0 * * * *"), "at minute 0 of every hour");
+    assert_eq!(describe("0 0 * * *"), "at 00:00");
+    assert_eq!(describe("0 8,12,18 * * *"), "at 08:00, 12:00 and 18:00");
+    assert_eq!(describe("*/10 * * * * *"), "every 10 seconds");
+    assert_eq!(describe("30 5 4 * * *"), "at 04:05:30");
+    assert_eq!(
+        describe("5,20,40 */2 * * *"),
+        "at minutes 5, 20 and 40, every 2 hours"
+    );
+    assert_eq!(
+        describe("5/15 * * * *"),
+        "every 15 minutes starting at minute 5"
+    );
+}
+
+#[test]
+fn describes_day_fields() {
+    assert_eq!(
+        describe("0 9 1,15 * MON"),
+        "at 09:00, on days 1 and 15 of the month or Monday"
+    );
+    assert_eq!(
+        describe("30 8 L * ?"),
+        "at 08:30, on the last day of the month"
+    );
+    assert_eq!(
+        describe("0 12 * JAN-MAR,DEC 2#2"),
+        "at 12:00, on the second Tuesday of the month, in January through March and December"
+    );
+    assert_eq!(
+        describe("0 0 9 13 * 5 2026-2027"),
+        "at 09:00, on day 13 of the month or Friday, in 2026 and 2027"
+    );
+
+    let mut strict = CronParser::new().parse("0 0 13 * 5").unwrap();
+    strict.day_match = croner::parser::DayMatch::And;
+    assert_eq!(
+        strict.describe(),
+        "at 00:00, on day 13 of the month if it is Friday"
+    );
+}
+
+#[test]
+fn describes_other_schedule_kinds() {
+    assert_eq!(describe("@every 90m"), "every 1h30m");
+    assert_eq!(
+        describe("@at 2026-11-01T03:00:00"),
+        "once at 2026-11-01 03:00:00"
+    );
+    assert_eq!(describe("@reboot"), "once when croner starts");
+}
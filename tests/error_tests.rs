@@ -0,0 +1,219 @@
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use croner::{
+    error::{ConfigError, CronError, Field},
+    loader::load_config,
+    parser::CronParser,
+};
+
+fn temp_config(s: &str) -> PathBuf {
+    let mut p = std::env::temp_dir();
+    let nanos = SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_nanos();
+    p.push(format!("errors.croner_{}", nanos));
+    fs::write(&p, s).expect("write failed");
+    p
+}
+
+#[test]
+fn cron_errors_point_at_the_bad_token() {
+    let p = CronParser::new();
+
+    let err = p.parse("*/5 9-25 * * MON").unwrap_err();
+    assert_eq!(
+        err,
+        CronError::OutOfRange {
+            field: Field::Hour,
+            value: "25".into(),
+            span: 6..8,
+        }
+    );
+
+    let err = p.parse("  0 0 1 JUN FRY").unwrap_err();
+    assert_eq!(
+        err,
+        CronError::InvalidValue {
+            field: Field::Weekday,
+            value: "FRY".into(),
+            span: 12..15,
+        }
+    );
+
+    let err = p.parse("0 0,15/0 * * *").unwrap_err();
+    assert!(matches!(
+        err,
+        CronError::InvalidStep {
+            field: Field::Hour,
+            ..
+        }
+    ));
+    assert_eq!(err.span(), 4..8);
+
+    // A missing endpoint spoils the whole range
+    let cases = [
+        ("1- * * * *", Field::Minute, "1-", 0..2),
+        ("0 -5 * * *", Field::Hour, "-5", 2..4),
+        ("0 0 - * *", Field::Day, "-", 4..5),
+        ("0 0 0 * * * 2020-", Field::Year, "2020-", 12..17),
+    ];
+    for (expr, field, value, span) in cases {
+        let err = p.parse(expr).unwrap_err();
+        assert_eq!(
+            err,
+            CronError::InvalidRange {
+                field,
+                value: value.into(),
+                span,
+            }
+        );
+    }
+    let expr = "1- * * * *";
+    let err = p.parse(expr).unwrap_err();
+    assert_eq!(
+        err.render(expr),
+        "Invalid range: 1-\n |\n | 1- * * * *\n | ^^"
+    );
+
+    let err = p.parse("0 0 0 * * * 2100").unwrap_err();
+    assert!(matches!(
+        err,
+        CronError::OutOfRange {
+            field: Field::Year,
+            ..
+        }
+    ));
+
+    let err = p.parse("0 0 * *").unwrap_err();
+    assert_eq!(
+        err,
+        CronError::FieldCount {
+            found: 4,
+            span: 0..7
+        }
+    );
+
+    let err = p.parse_schedule("@every 5x").unwrap_err();
+    assert_eq!(err.span(), 7..9);
+}
+
+#[test]
+fn renders_cron_errors_with_carets() {
+    let expr = "*/5 9-25 * * MON";
+    let err = CronParser::new().parse(expr).unwrap_err();
+    assert_eq!(
+        err.render(expr),
+        "Invalid value: 25 (hour must be 0-23)\n |\n | */5 9-25 * * MON\n |       ^^"
+    );
+}
+
+#[test]
+fn config_errors_carry_line_and_column() {
+    let p = temp_config("[job:a]\nschedule = * * * * *\ncommand = echo hi\n  wat = huh\n");
+    match &load_config(&p).unwrap_err().errors()[0] {
+        ConfigError::UnknownKey { line, col, key } => {
+            assert_eq!((*line, *col, key.as_str()), (4, 3, "wat"));
+        }
+        other => panic!("unexpected error: {}", other),
+    }
+
+    let p = temp_config("[job:a]\nschedule = 0 0 32 * *\ncommand = echo hi\n");
+    let errors = load_config(&p).unwrap_err();
+    let err = &errors.errors()[0];
+    assert!(matches!(
+        err,
+        ConfigError::InvalidSchedule {
+            line: 2,
+            col: 12,
+            ..
+        }
+    ));
+    assert!(err.source().is_some());
+}
+
+#[test]
+fn renders_config_errors_with_the_source_line() {
+    let text = "[job:a]\nschedule = 0 0 32 * *\ncommand = echo hi\n";
+    let p = temp_config(text);
+    let err = load_config(&p).unwrap_err();
+    assert_eq!(
+        err.render(text),
+        "line 2: job 'a': invalid schedule: Invalid value: 32 (day of month must be 1-31)\n  |\n2 | schedule = 0 0 32 * *\n  |                ^^"
+    );
+
+    let text = "[job:a]\nschedule = * * * * *\n";
+    let p = temp_config(text);
+    let err = load_config(&p).unwrap_err();
+    assert!(matches!(
+        err.errors(),
+        [ConfigError::MissingKey { key: "command", .. }]
+    ));
+    assert_eq!(
+        err.render(text),
+        "line 1: job 'a': missing command\n  |\n1 | [job:a]"
+    );
+}
+
+#[test]
+fn reports_every_config_error_in_line_order() {
+    let text = "\
+[job:a]
+schedule = 0 0 32 * *
+command = echo hi
+wat = 1
+
+[job:b]
+schedule = @every 5m
+
+[settings]
+timezone = Nowhere/Zone
+
+[settings]
+timezone = UTC
+
+[job:a]
+schedule = * * * * *
+command = echo again
+";
+    let p = temp_config(text);
+    let errors = load_config(&p).unwrap_err();
+    let lines: Vec<Option<usize>> = errors.errors().iter().map(ConfigError::line).collect();
+    assert_eq!(
+        lines,
+        [Some(2), Some(4), Some(6), Some(10), Some(12), Some(15)]
+    );
+
+    let kinds = errors.errors();
+    assert!(matches!(kinds[0], ConfigError::InvalidSchedule { .. }));
+    assert!(matches!(kinds[1], ConfigError::UnknownKey { .. }));
+    assert!(matches!(
+        kinds[2],
+        ConfigError::MissingKey { key: "command", .. }
+    ));
+    assert!(matches!(kinds[3], ConfigError::InvalidValue { .. }));
+    assert!(matches!(kinds[4], ConfigError::DuplicateSection { .. }));
+    assert!(matches!(kinds[5], ConfigError::DuplicateJobId { .. }));
+    assert_eq!(errors.to_string().lines().count(), 6);
+}
+
+#[test]
+fn rejected_values_are_not_reported_twice() {
+    // The empty command is the only problem, not also a missing command
+    let p = temp_config("[job:a]\nschedule = * * * * *\ncommand =\n");
+    let errors = load_config(&p).unwrap_err();
+    assert_eq!(errors.errors().len(), 1, "got: {}", errors);
+    assert!(errors.to_string().contains("command cannot be empty"));
+
+    // Keys under a rejected header are skipped rather than misattributed
+    let p = temp_config("[job:a]\nschedule = * * * * *\ncommand = x\n[jobs:b]\nschedule = bad\n");
+    let errors = load_config(&p).unwrap_err();
+    assert_eq!(errors.errors().len(), 1, "got: {}", errors);
+    assert!(matches!(
+        errors.errors()[0],
+        ConfigError::InvalidSection { line: 4, .. }
+    ));
+}
@@ -5,8 +5,11 @@ use std::thread::sleep;
 use std::time::{Duration, SystemTime};
 
 use croner::{
-    loader::{load_config, ConfigCache},
-    models::Fanout,
+    datetime::DateTime,
+    loader::{load, load_config, ConfigCache},
+    models::{Fanout, Overlap, DEFAULT_KILL_GRACE, MAX_JITTER},
+    parser::{DayMatch, DstPolicy, Schedule},
+    shell::Shell,
 };
 
 fn temp_path(name: &str) -> PathBuf {
@@ -128,7 +131,7 @@ wat = huh
 "#,
     );
 
-    let err = load_config(&p).unwrap_err();
+    let err = load_config(&p).unwrap_err().to_string();
     assert!(err.to_lowercase().contains("unknown key"));
 }
 
@@ -145,7 +148,7 @@ command = echo hi
 "#,
     );
 
-    let err = load_config(&p).unwrap_err();
+    let err = load_config(&p).unwrap_err().to_string();
     assert!(err.to_lowercase().contains("duplicate `schedule`"));
 }
 
@@ -160,7 +163,7 @@ command = echo hi
 "#,
     );
 
-    let err = load_config(&p).unwrap_err();
+    let err = load_config(&p).unwrap_err().to_string();
     assert!(err.to_lowercase().contains("missing schedule"));
 
     write(
@@ -170,7 +173,7 @@ command = echo hi
 schedule = * * * * *
 "#,
     );
-    let err = load_config(&p).unwrap_err();
+    let err = load_config(&p).unwrap_err().to_string();
     assert!(err.to_lowercase().contains("missing command"));
 }
 
@@ -187,7 +190,7 @@ fanout = 2
 fanout = ["--x"]
 "#,
     );
-    let err = load_config(&p).unwrap_err();
+    let err = load_config(&p).unwrap_err().to_string();
     assert!(err.to_lowercase().contains("duplicate `fanout`"));
 }
 
@@ -203,7 +206,7 @@ command = echo hi
 fanout = nope
 "#,
     );
-    let err = load_config(&p).unwrap_err();
+    let err = load_config(&p).unwrap_err().to_string();
     assert!(err
         .to_lowercase()
         .contains("fanout must be an integer or list"));
@@ -225,7 +228,7 @@ command = echo hi
 "#,
     );
 
-    let err = load_config(&p).unwrap_err();
+    let err = load_config(&p).unwrap_err().to_string();
     assert!(err.to_lowercase().contains("duplicate job id"));
 }
 
@@ -268,6 +271,11 @@ schedule = * * * * *
     assert_eq!(cache.jobs.len(), 1);
     assert_eq!(cache.jobs[0].id, "a");
 
+    // reported once, not re-parsed until the file changes again
+    let changed = cache.reload_if_changed(&p).expect("failed file unchanged");
+    assert!(!changed);
+    assert_eq!(cache.jobs[0].id, "a");
+
     // fix file → reload true, jobs updated
     write(
         &p,
@@ -281,4 +289,569 @@ command = echo bye
     assert!(changed);
     assert_eq!(cache.jobs.len(), 1);
     assert_eq!(cache.jobs[0].id, "b");
+
+    // a missing file is reported once too
+    std::fs::remove_file(&p).unwrap();
+    assert!(cache.reload_if_changed(&p).is_err());
+    assert!(!cache.reload_if_changed(&p).expect("still missing"));
+    assert_eq!(cache.jobs[0].id, "b");
+}
+
+#[test]
+fn timezone_defaults_and_overrides() {
+    let p = temp_path("tz.croner");
+    write(
+        &p,
+        r#"
+[job:a]
+schedule = 0 2 * * *
+command = echo hi
+
+[job:b]
+schedule = 0 2 * * *
+command = echo hi
+timezone = CET-1CEST,M3.5.0,M10.5.0/3
+
+[settings]
+timezone = UTC
+"#,
+    );
+
+    let jobs = load_config(&p).expect("parse");
+    assert_eq!(jobs[0].timezone.name(), "UTC");
+    assert_eq!(jobs[1].timezone.name(), "CET-1CEST,M3.5.0,M10.5.0/3");
+}
+
+#[test]
+fn error_on_unknown_timezone() {
+    let p = temp_path("badtz.croner");
+    write(
+        &p,
+        r#"
+[job:a]
+schedule = * * * * *
+command = echo hi
+timezone = Mars/Olympus_Mons
+"#,
+    );
+
+    let err = load_config(&p).unwrap_err().to_string();
+    assert!(err.contains("line 5"), "got err: {}", err);
+    assert!(err.contains("unknown time zone"), "got err: {}", err);
+}
+
+#[test]
+fn dst_policy_defaults_and_overrides() {
+    let p = temp_path("dst.croner");
+    write(
+        &p,
+        r#"
+[settings]
+dst_policy = skip
+
+[job:a]
+schedule = 30 2 * * *
+command = echo hi
+
+[job:b]
+schedule = 30 2 * * *
+command = echo hi
+dst_policy = twice
+"#,
+    );
+
+    let jobs = load_config(&p).expect("parse");
+    assert_eq!(jobs[0].dst_policy, DstPolicy::Skip);
+    assert_eq!(jobs[1].dst_policy, DstPolicy::Twice);
+
+    write(
+        &p,
+        r#"
+[job:a]
+schedule = 30 2 * * *
+command = echo hi
+dst_policy = sometimes
+"#,
+    );
+    let err = load_config(&p).unwrap_err().to_string();
+    assert!(
+        err.contains("dst_policy must be one of"),
+        "got err: {}",
+        err
+    );
+}
+
+#[test]
+fn day_match_defaults_to_or() {
+    let p = temp_path("daymatch.croner");
+    write(
+        &p,
+        r#"
+[job:a]
+schedule = 0 0 1,15 * 1
+command = echo hi
+
+[job:b]
+schedule = 0 0 13 * 5
+command = echo hi
+day_match = and
+"#,
+    );
+
+    let jobs = load_config(&p).expect("parse");
+    let day_match = |i: usize| match &jobs[i].schedule {
+        Schedule::Cron(c) => c.day_match,
+        other => panic!("expected cron schedule, got {:?}", other),
+    };
+    assert_eq!(day_match(0), DayMatch::Or);
+    assert_eq!(day_match(1), DayMatch::And);
+}
+
+#[test]
+fn hash_starts_a_comment_only_after_whitespace() {
+    let p = temp_path("nth_weekday.croner");
+    write(
+        &p,
+        "# header\n[job:a] # trailing\nschedule = 0 9 * * MON#2   # second Monday\ncommand = echo hi #1\n",
+    );
+
+    let jobs = load_config(&p).expect("parse");
+    let Schedule::Cron(cron) = &jobs[0].schedule else {
+        panic!("expected cron schedule, got {:?}", jobs[0].schedule);
+    };
+    let first = cron
+        .next_match(&DateTime::new(2025, 6, 1, 0, 0, 0))
+        .unwrap();
+    assert_eq!(first, DateTime::new(2025, 6, 9, 9, 0, 0));
+    let second = cron.next_match(&first).unwrap();
+    assert_eq!(second, DateTime::new(2025, 7, 14, 9, 0, 0));
+    assert_eq!(jobs[0].base_cmd, ["echo", "hi"]);
+}
+
+#[test]
+fn loads_macros_and_reboot() {
+    let p = temp_path("macros.croner");
+    write(
+        &p,
+        r#"
+[job:nightly]
+schedule = @daily
+command = echo hi
+
+[job:boot]
+schedule = @reboot
+command = echo booted
+"#,
+    );
+
+    let jobs = load_config(&p).expect("parse");
+    assert!(matches!(jobs[0].schedule, Schedule::Cron(_)));
+    assert!(matches!(jobs[1].schedule, Schedule::Reboot));
+}
+
+#[test]
+fn loads_interval_schedules_with_anchor() {
+    let p = temp_path("interval.croner");
+    write(
+        &p,
+        r#"
+[job:a]
+schedule = @every 7m
+command = echo hi
+
+[job:b]
+schedule = @every 1h30m
+timezone = UTC
+anchor = 2025-01-01 00:15
+command = echo hi
+"#,
+    );
+
+    let jobs = load_config(&p).expect("parse");
+    match &jobs[0].schedule {
+        Schedule::Interval(i) => assert_eq!((i.every, i.anchor), (420, None)),
+        other => panic!("expected interval schedule, got {:?}", other),
+    }
+    match &jobs[1].schedule {
+        Schedule::Interval(i) => assert_eq!((i.every, i.anchor), (5400, Some(1_735_690_500))),
+        other => panic!("expected interval schedule, got {:?}", other),
+    }
+}
+
+#[test]
+fn anchor_requires_an_interval_schedule() {
+    let p = temp_path("anchor_cron.croner");
+    write(
+        &p,
+        r#"
+[job:a]
+schedule = @hourly
+anchor = 2025-01-01 00:15
+command = echo hi
+"#,
+    );
+    let err = load_config(&p).unwrap_err().to_string();
+    assert!(err.contains("only applies to `@every`"), "got err: {}", err);
+
+    write(
+        &p,
+        r#"
+[job:a]
+schedule = @every 5m
+anchor = tomorrow
+command = echo hi
+"#,
+    );
+    let err = load_config(&p).unwrap_err().to_string();
+    assert!(err.contains("invalid anchor"), "got err: {}", err);
+}
+
+#[test]
+fn hashed_schedules_follow_the_job_id() {
+    let p = temp_path("hashed.croner");
+    let mut config = String::new();
+    for i in 0..20 {
+        config.push_str(&format!(
+            "[job:worker-{}]\nschedule = H * * * *\ncommand = echo hi\n\n",
+            i
+        ));
+    }
+    write(&p, &config);
+
+    let minutes = |jobs: &[croner::models::JobSpec]| -> Vec<u8> {
+        jobs.iter()
+            .map(|j| match &j.schedule {
+                Schedule::Cron(c) => c.minutes[0],
+                other => panic!("expected cron schedule, got {:?}", other),
+            })
+            .collect()
+    };
+
+    let first = minutes(&load_config(&p).expect("parse"));
+    let second = minutes(&load_config(&p).expect("parse"));
+    assert_eq!(first, second);
+
+    let distinct: std::collections::HashSet<_> = first.iter().collect();
+    assert!(
+        distinct.len() > 5,
+        "only {} distinct minutes",
+        distinct.len()
+    );
+}
+
+#[test]
+fn loads_jitter() {
+    let p = temp_path("jitter.croner");
+    write(
+        &p,
+        r#"
+[job:a]
+schedule = 0 * * * *
+command = echo hi
+jitter = 1m30s
+
+[job:b]
+schedule = 0 * * * *
+command = echo hi
+"#,
+    );
+
+    let jobs = load_config(&p).expect("parse");
+    assert_eq!(jobs[0].jitter, Duration::from_secs(90));
+    assert_eq!(jobs[1].jitter, Duration::ZERO);
+
+    write(
+        &p,
+        "[job:a]\nschedule = 0 * * * *\ncommand = echo hi\njitter = 99999999999999d\n",
+    );
+    let jobs = load_config(&p).expect("parse");
+    assert_eq!(jobs[0].jitter, MAX_JITTER);
+
+    write(
+        &p,
+        r#"
+[job:a]
+schedule = 0 * * * *
+command = echo hi
+jitter = soon
+"#,
+    );
+    let err = load_config(&p).unwrap_err().to_string();
+    assert!(err.contains("invalid jitter"), "got err: {}", err);
+}
+
+#[test]
+fn loads_timeouts() {
+    let p = temp_path("timeout.croner");
+    write(
+        &p,
+        r#"
+[job:a]
+schedule = 0 * * * *
+command = python etl.py
+timeout = 10m
+kill_grace = 5s
+
+[job:b]
+schedule = 0 * * * *
+command = echo hi
+"#,
+    );
+
+    let jobs = load_config(&p).expect("parse");
+    assert_eq!(jobs[0].timeout, Some(Duration::from_secs(600)));
+    assert_eq!(jobs[0].kill_grace, Duration::from_secs(5));
+    assert_eq!(jobs[1].timeout, None);
+    assert_eq!(jobs[1].kill_grace, DEFAULT_KILL_GRACE);
+
+    write(
+        &p,
+        "[job:a]\nschedule = * * * * *\ncommand = echo hi\nkill_grace = 0s\njitter = 0s\n",
+    );
+    let jobs = load_config(&p).expect("parse");
+    assert_eq!(jobs[0].kill_grace, Duration::ZERO);
+    assert_eq!(jobs[0].jitter, Duration::ZERO);
+
+    write(
+        &p,
+        "[job:a]\nschedule = * * * * *\ncommand = echo hi\ntimeout = 0s\n",
+    );
+    let err = load_config(&p).unwrap_err().to_string();
+    assert!(err.contains("invalid timeout"), "got err: {}", err);
+
+    write(
+        &p,
+        r#"
+[job:a]
+schedule = 0 * * * *
+command = echo hi
+timeout = forever
+kill_grace = 1s
+kill_grace = 2s
+"#,
+    );
+    let err = load_config(&p).unwrap_err().to_string();
+    assert!(err.contains("line 5: invalid timeout"), "got err: {}", err);
+    assert!(
+        err.contains("line 7: duplicate `kill_grace`"),
+        "got err: {}",
+        err
+    );
+}
+
+#[test]
+fn loads_overlap_policy() {
+    let p = temp_path("overlap.croner");
+    write(
+        &p,
+        r#"
+[job:a]
+schedule = * * * * *
+command = python etl.py
+overlap = queue
+
+[job:b]
+schedule = * * * * *
+command = echo hi
+"#,
+    );
+
+    let jobs = load_config(&p).expect("parse");
+    assert_eq!(jobs[0].overlap, Overlap::Queue);
+    assert_eq!(jobs[1].overlap, Overlap::Allow);
+
+    write(
+        &p,
+        "[job:a]\nschedule = * * * * *\ncommand = echo hi\noverlap = never\n",
+    );
+    let err = load_config(&p).unwrap_err().to_string();
+    assert!(err.contains("overlap must be one of"), "got err: {}", err);
+}
+
+#[test]
+fn loads_concurrency_limits() {
+    let p = temp_path("limits.croner");
+    write(
+        &p,
+        r#"
+[settings]
+max_concurrent = 4
+
+[job:a]
+schedule = * * * * *
+command = python etl.py
+fanout = 20
+max_parallel = 2
+
+[job:b]
+schedule = * * * * *
+command = echo hi
+"#,
+    );
+
+    let config = load(&p).expect("parse");
+    assert_eq!(config.max_concurrent, Some(4));
+    assert_eq!(config.jobs[0].max_parallel, Some(2));
+    assert_eq!(config.jobs[1].max_parallel, None);
+
+    let mut cache = ConfigCache::new();
+    assert!(cache.reload_if_changed(&p).expect("reload"));
+    assert_eq!(cache.max_concurrent, Some(4));
+
+    write(
+        &p,
+        "[settings]\nmax_concurrent = 0\n\n[job:a]\nschedule = * * * * *\ncommand = echo hi\nmax_parallel = many\n",
+    );
+    let err = load_config(&p).unwrap_err().to_string();
+    assert!(
+        err.contains("line 2: max_concurrent must be a positive integer"),
+        "got err: {}",
+        err
+    );
+    assert!(
+        err.contains("line 7: max_parallel must be a positive integer"),
+        "got err: {}",
+        err
+    );
+}
+
+#[test]
+fn loads_date_window() {
+    let p = temp_path("window.croner");
+    write(
+        &p,
+        r#"
+[job:campaign]
+schedule = 0 9 * * *
+timezone = UTC
+not_before = 2025-11-01 00:00
+not_after = 2025-11-30 23:59:59
+command = echo hi
+"#,
+    );
+
+    let jobs = load_config(&p).expect("parse");
+    let unix = |t: SystemTime| t.duration_since(std::time::UNIX_EPOCH).unwrap().as_secs();
+    assert_eq!(jobs[0].not_before.map(unix), Some(1_761_955_200));
+    assert_eq!(jobs[0].not_after.map(unix), Some(1_764_547_199));
+
+    write(
+        &p,
+        r#"
+[job:campaign]
+schedule = 0 9 * * *
+not_before = 2025-12-01 00:00
+not_after = 2025-11-01 00:00
+command = echo hi
+"#,
+    );
+    let err = load_config(&p).unwrap_err().to_string();
+    assert!(err.contains("later than"), "got err: {}", err);
+}
+
+#[test]
+fn loads_calendars_and_exclusions() {
+    let p = temp_path("calendars.croner");
+    write(
+        &p,
+        r#"
+[job:payroll]
+schedule = 0 9 * * *
+exclude = [holidays, shutdown]
+command = echo pay
+
+[calendar:holidays]
+dates = [2025-12-25, 2026-01-01]
+dates = 2025-05-01
+
+[calendar:shutdown]
+dates = [2025-08-01..2025-08-15]
+"#,
+    );
+
+    let jobs = load_config(&p).expect("parse");
+    let Schedule::Cron(cron) = &jobs[0].schedule else {
+        panic!("expected cron schedule, got {:?}", jobs[0].schedule);
+    };
+    let names: Vec<&str> = cron.exclude.iter().map(|c| c.name.as_str()).collect();
+    assert_eq!(names, ["holidays", "shutdown"]);
+    assert!(cron.exclude[0].contains(2025, 5, 1));
+    assert!(cron.exclude[1].contains(2025, 8, 10));
+
+    write(
+        &p,
+        r#"
+[job:payroll]
+schedule = 0 9 * * *
+exclude = holidays
+command = echo pay
+"#,
+    );
+    let err = load_config(&p).unwrap_err().to_string();
+    assert!(
+        err.contains("unknown calendar 'holidays'"),
+        "got err: {}",
+        err
+    );
+
+    write(
+        &p,
+        r#"
+[calendar:holidays]
+dates = 2025-13-01
+"#,
+    );
+    let err = load_config(&p).unwrap_err().to_string();
+    assert!(err.contains("line 3"), "got err: {}", err);
+}
+
+#[test]
+fn commands_run_directly_unless_a_shell_is_set() {
+    let p = temp_path("shell.croner");
+    write(
+        &p,
+        r#"
+[job:direct]
+schedule = * * * * *
+command = echo "a b" 'c'
+fanout = ["x y"]
+
+[job:piped]
+schedule = * * * * *
+shell = sh
+command = echo hi | wc -c
+fanout = ["> /dev/null"]
+"#,
+    );
+
+    let jobs = load_config(&p).expect("parse");
+    assert_eq!(jobs[0].shell, Shell::None);
+    assert_eq!(jobs[0].base_cmd, ["echo", "a b", "c"]);
+    match &jobs[0].fanout {
+        Fanout::List(list) => assert_eq!(list[0], ["echo", "a b", "c", "x", "y"]),
+        other => panic!("expected list fanout, got {:?}", other),
+    }
+
+    assert_eq!(jobs[1].shell, Shell::Sh);
+    assert_eq!(jobs[1].base_cmd, ["echo hi | wc -c"]);
+    match &jobs[1].fanout {
+        Fanout::List(list) => assert_eq!(list[0], ["echo hi | wc -c > /dev/null"]),
+        other => panic!("expected list fanout, got {:?}", other),
+    }
+
+    write(
+        &p,
+        "[job:a]\nschedule = * * * * *\nshell = zsh\ncommand = echo 'oops\n",
+    );
+    let err = load_config(&p).unwrap_err().to_string();
+    assert!(
+        err.contains("line 3: shell must be one of"),
+        "got err: {}",
+        err
+    );
+    assert!(
+        err.contains("line 4: job 'a': invalid command: unclosed quote"),
+        "got err: {}",
+        err
+    );
 }
@@ -1,6 +1,7 @@
 // tests/cron_parser_tests.rs
 
-use croner::parser::CronParser;
+use croner::error::{CronError, Field};
+use croner::parser::{format_duration, CronParser, CronSchedule, Schedule};
 
 fn bits_set(mut mask: u64, max_bit: u8) -> Vec<u8> {
     let mut out = Vec::new();
@@ -101,7 +102,7 @@ fn mixes_lists_ranges_wildcards_and_steps() {
 }
 
 #[test]
-fn trims_and_requires_five_fields() {
+fn trims_and_requires_five_to_seven_fields() {
     let p = CronParser::new();
 
     // Trimming leading/trailing spaces should work
@@ -109,12 +110,47 @@ fn trims_and_requires_five_fields() {
     assert_has_bits(s.minute, &[0], 59);
 
     // Not enough fields
-    let err = p.parse("0 0 1 1").unwrap_err();
-    assert!(err.contains("Expected 5 fields"), "got err: {}", err);
+    let err = p.parse("0 0 1 1").unwrap_err().to_string();
+    assert!(
+        err.contains("Expected 5, 6 or 7 fields"),
+        "got err: {}",
+        err
+    );
 
     // Too many fields
-    let err = p.parse("0 0 1 1 0 extra").unwrap_err();
-    assert!(err.contains("Expected 5 fields"), "got err: {}", err);
+    let err = p.parse("0 0 0 1 1 0 2030 extra").unwrap_err().to_string();
+    assert!(
+        err.contains("Expected 5, 6 or 7 fields"),
+        "got err: {}",
+        err
+    );
+
+    // A seventh field is a trailing year field, so it must be valid
+    let err = p.parse("0 0 0 1 1 0 extra").unwrap_err().to_string();
+    assert!(err.contains("Invalid value"), "got err: {}", err);
+
+    // A sixth field is a leading seconds field, so it must be valid
+    let err = p.parse("0 0 1 1 0 extra").unwrap_err().to_string();
+    assert!(err.contains("Invalid value"), "got err: {}", err);
+}
+
+#[test]
+fn parses_optional_seconds_field() {
+    let p = CronParser::new();
+
+    // Five fields fire at second 0
+    let s = p.parse("* * * * *").unwrap();
+    assert_has_bits(s.second, &[0], 59);
+    assert_eq!(s.seconds, vec![0]);
+
+    // Six fields: seconds come first
+    let s = p.parse("*/10 5 * * * *").unwrap();
+    assert_has_bits(s.second, &[0, 10, 20, 30, 40, 50], 59);
+    assert_has_bits(s.minute, &[5], 59);
+    assert_eq!(bits_set(s.hour as u64, 23).len(), 24);
+
+    let err = p.parse("60 * * * * *").unwrap_err().to_string();
+    assert!(err.contains("Invalid value"), "got err: {}", err);
 }
 
 #[test]
@@ -122,23 +158,23 @@ fn rejects_out_of_range_values() {
     let p = CronParser::new();
 
     // minute 60 invalid
-    let err = p.parse("60 0 1 1 0").unwrap_err();
+    let err = p.parse("60 0 1 1 0").unwrap_err().to_string();
     assert!(err.contains("Invalid value"), "got err: {}", err);
 
     // hour 24 invalid
-    let err = p.parse("0 24 1 1 0").unwrap_err();
+    let err = p.parse("0 24 1 1 0").unwrap_err().to_string();
     assert!(err.contains("Invalid value"), "got err: {}", err);
 
     // day 0 invalid (days are 1..31)
-    let err = p.parse("0 0 0 1 0").unwrap_err();
+    let err = p.parse("0 0 0 1 0").unwrap_err().to_string();
     assert!(err.contains("Invalid value"), "got err: {}", err);
 
     // month 13 invalid
-    let err = p.parse("0 0 1 13 0").unwrap_err();
+    let err = p.parse("0 0 1 13 0").unwrap_err().to_string();
     assert!(err.contains("Invalid value"), "got err: {}", err);
 
-    // weekday 7 invalid
-    let err = p.parse("0 0 1 1 7").unwrap_err();
+    // weekday 8 invalid (7 is Sunday)
+    let err = p.parse("0 0 1 1 8").unwrap_err().to_string();
     assert!(err.contains("Invalid value"), "got err: {}", err);
 }
 
@@ -147,23 +183,30 @@ fn rejects_bad_ranges() {
     let p = CronParser::new();
 
     // reversed
-    let err = p.parse("10-5 0 1 1 0").unwrap_err();
+    let err = p.parse("10-5 0 1 1 0").unwrap_err().to_string();
     assert!(err.contains("Invalid range"), "got err: {}", err);
 
     // low below start (day must be >=1)
-    let err = p.parse("0 0 0-5 1 0").unwrap_err();
+    let err = p.parse("0 0 0-5 1 0").unwrap_err().to_string();
     assert!(
         err.contains("Invalid value") || (err.contains("Invalid range")),
         "got err: {}",
         err
     );
 
-    // high above end (month must be <=12)
+    // high above end (month must be <=12): the bad endpoint is reported
     let err = p.parse("0 0 1 10-15 0").unwrap_err();
-    assert!(err.contains("Invalid range"), "got err: {}", err);
+    assert_eq!(
+        err,
+        CronError::OutOfRange {
+            field: Field::Month,
+            value: "15".into(),
+            span: 9..11,
+        }
+    );
 
     // malformed "a-b-c"
-    let err = p.parse("0 0 1 a-b-c 0").unwrap_err();
+    let err = p.parse("0 0 1 a-b-c 0").unwrap_err().to_string();
     assert!(
         err.contains("Invalid range") || (err.contains("Invalid value")),
         "got err: {}",
@@ -175,20 +218,25 @@ fn rejects_bad_ranges() {
 fn rejects_non_numeric_parts() {
     let p = CronParser::new();
 
-    let err = p.parse("x * * * *").unwrap_err();
+    let err = p.parse("x * * * *").unwrap_err().to_string();
     assert!(err.contains("Invalid value"), "got err: {}", err);
 
-    let err = p.parse("* y * * *").unwrap_err();
+    let err = p.parse("* y * * *").unwrap_err().to_string();
     assert!(err.contains("Invalid value"), "got err: {}", err);
 
-    let err = p.parse("* * z * *").unwrap_err();
+    let err = p.parse("* * z * *").unwrap_err().to_string();
     assert!(err.contains("Invalid value"), "got err: {}", err);
 
-    let err = p.parse("* * * w *").unwrap_err();
+    let err = p.parse("* * * w *").unwrap_err().to_string();
     assert!(err.contains("Invalid value"), "got err: {}", err);
 
-    let err = p.parse("* * * * v").unwrap_err();
+    let err = p.parse("* * * * v").unwrap_err().to_string();
     assert!(err.contains("Invalid value"), "got err: {}", err);
+
+    // Cron numbers carry no sign
+    for expr in ["+5 * * * *", "* +05 * * *", "*/+5 * * * *", "* * 1-+3 * *"] {
+        assert!(p.parse(expr).is_err(), "{:?} should be rejected", expr);
+    }
 }
 
 #[test]
@@ -219,3 +267,340 @@ fn range_edges_and_overlaps() {
     assert_has_bits(s.month as u64, &[2, 3, 4], 12);
     assert_has_bits(s.weekday as u64, &[0, 1, 2, 3], 6);
 }
+
+#[test]
+fn records_day_field_restriction() {
+    let p = CronParser::new();
+
+    let s = p.parse("0 0 * * *").unwrap();
+    assert!(!s.day_restricted && !s.weekday_restricted);
+
+    let s = p.parse("0 0 1,15 * 1").unwrap();
+    assert!(s.day_restricted && s.weekday_restricted);
+
+    let s = p.parse("0 0 */2 * *").unwrap();
+    assert!(s.day_restricted && !s.weekday_restricted);
+}
+
+#[test]
+fn parses_month_and_weekday_names() {
+    let p = CronParser::new();
+
+    let s = p.parse("0 9 * JAN-MAR MON-FRI").unwrap();
+    assert_has_bits(s.month as u64, &[1, 2, 3], 12);
+    assert_has_bits(s.weekday as u64, &[1, 2, 3, 4, 5], 6);
+
+    // Case-insensitive, in lists and mixed with numbers.
+    let s = p.parse("0 0 * jun,Dec,2 sun,Sat").unwrap();
+    assert_has_bits(s.month as u64, &[2, 6, 12], 12);
+    assert_has_bits(s.weekday as u64, &[0, 6], 6);
+
+    // Names are only valid in their own field.
+    let err = p.parse("0 0 MON * *").unwrap_err().to_string();
+    assert!(err.contains("Invalid value"), "got err: {}", err);
+    let err = p.parse("0 0 * * JAN").unwrap_err().to_string();
+    assert!(err.contains("Invalid value"), "got err: {}", err);
+}
+
+#[test]
+fn seven_is_sunday() {
+    let p = CronParser::new();
+
+    let s = p.parse("0 0 * * 7").unwrap();
+    assert_has_bits(s.weekday as u64, &[0], 6);
+    assert_eq!(s.weekdays, vec![0]);
+
+    let s = p.parse("0 0 * * 5-7").unwrap();
+    assert_has_bits(s.weekday as u64, &[0, 5, 6], 6);
+
+    let s = p.parse("0 0 * * *").unwrap();
+    assert_eq!(s.weekdays, vec![0, 1, 2, 3, 4, 5, 6]);
+}
+
+#[test]
+fn parses_stepped_ranges_and_value_steps() {
+    let p = CronParser::new();
+
+    // Range with step
+    let s = p.parse("30-59/10 8-18/2 1-31/10 1-12/3 1-5/2").unwrap();
+    assert_has_bits(s.minute, &[30, 40, 50], 59);
+    assert_has_bits(s.hour as u64, &[8, 10, 12, 14, 16, 18], 23);
+    assert_has_bits(s.day as u64, &[1, 11, 21, 31], 31);
+    assert_has_bits(s.month as u64, &[1, 4, 7, 10], 12);
+    assert_has_bits(s.weekday as u64, &[1, 3, 5], 6);
+
+    // Start with step runs to the end of the field
+    let s = p.parse("5/15 0 1 1 1/2").unwrap();
+    assert_has_bits(s.minute, &[5, 20, 35, 50], 59);
+    assert_has_bits(s.weekday as u64, &[1, 3, 5], 6);
+
+    // Names work in stepped ranges too
+    let s = p.parse("0 0 1 JAN-DEC/6 MON-FRI/2").unwrap();
+    assert_has_bits(s.month as u64, &[1, 7], 12);
+    assert_has_bits(s.weekday as u64, &[1, 3, 5], 6);
+}
+
+#[test]
+fn rejects_bad_stepped_ranges() {
+    let p = CronParser::new();
+
+    let err = p.parse("10-50/0 * * * *").unwrap_err().to_string();
+    assert!(err.contains("Invalid step"), "got err: {}", err);
+
+    let err = p.parse("5/x * * * *").unwrap_err().to_string();
+    assert!(err.contains("Invalid step"), "got err: {}", err);
+
+    let err = p.parse("50-10/5 * * * *").unwrap_err().to_string();
+    assert!(err.contains("Invalid range"), "got err: {}", err);
+
+    let err = p.parse("60/5 * * * *").unwrap_err().to_string();
+    assert!(err.contains("Invalid value"), "got err: {}", err);
+}
+
+#[test]
+fn expands_schedule_macros() {
+    let p = CronParser::new();
+
+    for (name, expr) in [
+        ("@yearly", "0 0 1 1 *"),
+        ("@annually", "0 0 1 1 *"),
+        ("@monthly", "0 0 1 * *"),
+        ("@weekly", "0 0 * * 0"),
+        ("@daily", "0 0 * * *"),
+        ("@midnight", "0 0 * * *"),
+        ("@hourly", "0 * * * *"),
+    ] {
+        let a = p.parse(name).unwrap();
+        let b = p.parse(expr).unwrap();
+        assert_eq!(
+            (a.minute, a.hour, a.day, a.month, a.weekday),
+            (b.minute, b.hour, b.day, b.month, b.weekday),
+            "{} should equal {}",
+            name,
+            expr
+        );
+    }
+
+    let err = p.parse("@fortnightly").unwrap_err().to_string();
+    assert!(err.contains("Unknown macro"), "got err: {}", err);
+}
+
+#[test]
+fn reboot_is_a_schedule_but_not_a_cron_expression() {
+    let p = CronParser::new();
+
+    assert!(matches!(p.parse_schedule("@reboot"), Ok(Schedule::Reboot)));
+    assert!(matches!(p.parse_schedule("@hourly"), Ok(Schedule::Cron(_))));
+    assert!(p.parse("@reboot").is_err());
+}
+
+#[test]
+fn parses_calendar_relative_day_rules() {
+    use croner::parser::{DayRule, WeekdayRule};
+    let p = CronParser::new();
+
+    let c = p.parse("0 0 L,LW,L-2,15W * ?").unwrap();
+    assert_eq!(c.day, 0);
+    assert_eq!(
+        c.day_rules,
+        vec![
+            DayRule::Last(0),
+            DayRule::LastWeekday,
+            DayRule::Last(2),
+            DayRule::NearestWeekday(15)
+        ]
+    );
+    assert!(!c.weekday_restricted);
+
+    let c = p.parse("0 0 ? * 5L,FRIL,2#2,7#1").unwrap();
+    assert_eq!(c.weekday, 0);
+    assert_eq!(
+        c.weekday_rules,
+        vec![
+            WeekdayRule::Last(5),
+            WeekdayRule::Last(5),
+            WeekdayRule::Nth(2, 2),
+            WeekdayRule::Nth(0, 1)
+        ]
+    );
+    assert!(!c.day_restricted);
+
+    // Rules mix with plain values.
+    let c = p.parse("0 0 1,L * *").unwrap();
+    assert_eq!(bits_set(c.day as u64, 31), vec![1]);
+    assert_eq!(c.day_rules, vec![DayRule::Last(0)]);
+
+    for bad in ["0 0 32W * *", "0 0 L-31 * *", "0 0 * * 1#6", "0 0 * * 8L"] {
+        let err = p.parse(bad).unwrap_err().to_string();
+        assert!(err.contains("Invalid value"), "{}: got err: {}", bad, err);
+    }
+}
+
+#[test]
+fn parses_interval_schedules() {
+    use croner::parser::{parse_duration, parse_duration_or_zero};
+    let p = CronParser::new();
+
+    assert_eq!(parse_duration("90s"), Ok(90));
+    assert_eq!(parse_duration("1h30m"), Ok(5400));
+    assert_eq!(parse_duration("2d"), Ok(172_800));
+    for bad in ["", "0s", "5", "5x", "m", "1h 30m"] {
+        assert!(parse_duration(bad).is_err(), "{:?} should be rejected", bad);
+    }
+    assert_eq!(parse_duration_or_zero("0s"), Ok(0));
+    assert_eq!(parse_duration_or_zero("0m30s"), Ok(30));
+    assert!(parse_duration_or_zero("").is_err());
+
+    match p.parse_schedule("@every 45s") {
+        Ok(Schedule::Interval(i)) => assert_eq!((i.every, i.anchor), (45, None)),
+        other => panic!("expected interval schedule, got {:?}", other),
+    }
+    assert!(p.parse_schedule("@every").is_err());
+    assert!(p.parse_schedule("@every0s").is_err());
+
+    // Up to a hundred years, so ticks stay representable
+    assert!(p.parse_schedule("@every 36525d").is_ok());
+    for bad in ["@every 36526d", "@every 9223372036854775807s"] {
+        assert!(
+            matches!(
+                p.parse_schedule(bad),
+                Err(CronError::InvalidDuration { .. })
+            ),
+            "{:?} should be rejected",
+            bad
+        );
+    }
+}
+
+#[test]
+fn parses_one_shot_schedules() {
+    use croner::datetime::DateTime;
+    let p = CronParser::new();
+
+    match p.parse_schedule("@at 2026-11-01T03:00:00") {
+        Ok(Schedule::At(at)) => assert_eq!(at, DateTime::new(2026, 11, 1, 3, 0, 0)),
+        other => panic!("expected one-shot schedule, got {:?}", other),
+    }
+    assert!(p.parse_schedule("@at next tuesday").is_err());
+    assert!(p.parse_schedule("@at").is_err());
+}
+
+#[test]
+fn hashed_fields_are_stable_per_seed() {
+    let mut p = CronParser::new();
+
+    p.set_seed(12345);
+    let a = p.parse("H H(9-17) H * *").unwrap();
+    let b = p.parse("H H(9-17) H * *").unwrap();
+    assert_eq!((a.minute, a.hour, a.day), (b.minute, b.hour, b.day));
+    assert_eq!(a.minutes.len(), 1);
+    assert!(a.hours.len() == 1 && (9..=17).contains(&a.hours[0]));
+    assert!(a.days.len() == 1 && (1..=28).contains(&a.days[0]));
+
+    // `H/15` keeps four evenly spaced minutes, offset by the hash.
+    let c = p.parse("H/15 * * * *").unwrap();
+    assert_eq!(c.minutes.len(), 4);
+    let first = c.minutes[0];
+    assert!(first < 15);
+    assert_eq!(c.minutes, vec![first, first + 15, first + 30, first + 45]);
+
+    // `H(0-29)/10` stays within its range.
+    let d = p.parse("H(0-29)/10 * * * *").unwrap();
+    assert_eq!(d.minutes.len(), 3);
+    assert!(d.minutes.iter().all(|&m| m <= 29));
+
+    // Different seeds spread out.
+    let minutes: std::collections::HashSet<u8> = (0..40)
+        .map(|seed| {
+            p.set_seed(seed);
+            p.parse("H * * * *").unwrap().minutes[0]
+        })
+        .collect();
+    assert!(
+        minutes.len() > 10,
+        "only {} distinct minutes",
+        minutes.len()
+    );
+
+    for bad in ["H(30-10) * * * *", "H(5 * * * *"] {
+        let err = p.parse(bad).unwrap_err().to_string();
+        assert!(err.contains("Invalid range"), "{}: got err: {}", bad, err);
+    }
+    let err = p.parse("H(0-60) * * * *").unwrap_err().to_string();
+    assert!(err.contains("minute must be 0-59"), "got err: {}", err);
+}
+
+#[test]
+fn parses_year_field() {
+    let p = CronParser::new();
+
+    assert_eq!(p.parse("0 0 0 1 1 *").unwrap().years, None);
+    assert_eq!(p.parse("0 0 0 1 1 * *").unwrap().years, None);
+    assert_eq!(
+        p.parse("0 0 0 1 1 * 2026,2024-2025").unwrap().years,
+        Some(vec![2024, 2025, 2026])
+    );
+    assert_eq!(
+        p.parse("0 0 0 1 1 * 2090/4").unwrap().years,
+        Some(vec![2090, 2094, 2098])
+    );
+
+    for (bad, msg) in [
+        ("0 0 0 1 1 * 1969", "Invalid value"),
+        ("0 0 0 1 1 * 2100", "Invalid value"),
+        ("0 0 0 1 1 * 2030-2025", "Invalid range"),
+        ("0 0 0 1 1 * 2025/0", "Invalid step"),
+    ] {
+        let err = p.parse(bad).unwrap_err().to_string();
+        assert!(err.contains(msg), "{}: got err: {}", bad, err);
+    }
+}
+
+#[test]
+fn formats_canonical_expressions() {
+    let canonical = |expr: &str| CronParser::new().parse(expr).unwrap().to_string();
+
+    assert_eq!(canonical("0,15,30,45 * * * *"), "*/15 * * * *");
+    assert_eq!(canonical("0 9 * * 1,2,3,4,5"), "0 9 * * 1-5");
+    assert_eq!(canonical("0 0 1 JAN-MAR MON"), "0 0 1 1-3 1");
+    assert_eq!(canonical("5/20 * * * *"), "5/20 * * * *");
+    assert_eq!(canonical("0 0,12 * * *"), "0 */12 * * *");
+    assert_eq!(canonical("1,2,4,5,6 * * * *"), "1,2,4-6 * * * *");
+    assert_eq!(canonical("0 0 L,15W * 5L,1#2"), "0 0 L,15W * 5L,1#2");
+    assert_eq!(canonical("0 0 * * 0-6"), "0 0 * * 0-6");
+    assert_eq!(canonical("*/10 * * * * *"), "*/10 * * * * *");
+    assert_eq!(canonical("0 30 9 * * * 2030"), "0 30 9 * * * 2030");
+    assert_eq!(canonical("0 0 0 1 1 * 1970-2099"), "0 0 0 1 1 * 1970-2099");
+}
+
+#[test]
+fn display_round_trips_through_from_str() {
+    let exprs = [
+        "*/15 9-17 * * 1-5",
+        "0 0 1,15 * *",
+        "0 0 ? * 6L",
+        "0 12 LW * *",
+        "30 */5 * * * *",
+        "0 0 0 29 2 * 2028/4",
+        "@every 1h30m",
+        "@at 2030-01-01 09:00",
+        "@reboot",
+    ];
+    for expr in exprs {
+        let schedule: Schedule = expr.parse().unwrap();
+        let again: Schedule = schedule.to_string().parse().unwrap();
+        assert_eq!(schedule, again, "{} -> {}", expr, schedule);
+    }
+
+    let cron: CronSchedule = "0 9 * * MON-FRI".parse().unwrap();
+    assert_eq!(cron.to_string(), "0 9 * * 1-5");
+    assert!("61 * * * *".parse::<CronSchedule>().is_err());
+}
+
+#[test]
+fn formats_durations() {
+    assert_eq!(format_duration(45), "45s");
+    assert_eq!(format_duration(5400), "1h30m");
+    assert_eq!(format_duration(90_061), "1d1h1m1s");
+    assert_eq!(format_duration(0), "0s");
+}
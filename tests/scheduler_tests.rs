@@ -1,52 +1,25 @@
-use croner::parser::CronSchedule;
-use croner::scheduler::{compute_next_run, hash_id};
-use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
-
-/// Returns (minute, hour, weekday) like `compute_next_run` logic uses.
-fn now_components() -> (u8, u8, u8) {
-    let now = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap_or_default();
-
-    let secs = now.as_secs();
-    let minutes_total = secs / 60;
-    let minute = (minutes_total % 60) as u8;
-    let hour = ((minutes_total / 60) % 24) as u8;
-    let days_since_epoch = minutes_total / (60 * 24);
-    let weekday = ((days_since_epoch + 4) % 7) as u8;
-    (minute, hour, weekday)
-}
-
-/// Bitmask helpers for the actual CronSchedule field types.
-fn bit_u64(v: u8) -> u64 {
-    1u64 << v
-}
-fn bit_u32(v: u8) -> u32 {
-    1u32 << v
-}
-fn bit_u16(v: u8) -> u16 {
-    1u16 << v
-}
-fn bit_u8(v: u8) -> u8 {
-    1u8 << v
-}
-
-/// Helper to build a valid CronSchedule with only minute/hour/weekday constraints.
-fn make_schedule(minute_mask: u64, hour_mask: u32, weekday_mask: u8) -> CronSchedule {
-    CronSchedule {
-        minute: minute_mask,
-        hour: hour_mask,
-        day: u32::MAX,   // match any day-of-month
-        month: u16::MAX, // match any month
-        weekday: weekday_mask,
-        // fill in any extra fields your CronSchedule actually has here:
-        // these names are from your error message
-        minutes: Vec::new(),
-        hours: Vec::new(),
-        days: Vec::new(),
-        months: Vec::new(),
-        weekdays: Vec::new(),
-    }
+use croner::datetime::{system_time_to_unix, unix_to_system_time, DateTime};
+use croner::models::Overlap;
+use croner::parser::{CronParser, DayMatch, DstPolicy};
+use croner::scheduler::{
+    compute_next_run, hash_id, next_run_after, overlap_action, start_in_order, OverlapAction,
+};
+use croner::tz::TimeZone;
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, SystemTime};
+
+fn at(year: i32, month: u8, day: u8, hour: u8, minute: u8) -> SystemTime {
+    unix_to_system_time(DateTime::new(year, month, day, hour, minute, 0).to_unix())
+}
+
+fn next(expr: &str, after: SystemTime) -> Option<DateTime> {
+    let schedule = CronParser::new().parse(expr).unwrap();
+    next_run_after(&schedule, &TimeZone::utc(), DstPolicy::Shift, after)
+        .map(|t| DateTime::from_unix(system_time_to_unix(t)))
+}
+
+fn dt(year: i32, month: u8, day: u8, hour: u8, minute: u8) -> DateTime {
+    DateTime::new(year, month, day, hour, minute, 0)
 }
 
 #[test]
@@ -62,55 +35,475 @@ fn hash_id_is_stable_and_distinguishes() {
 }
 
 #[test]
-fn compute_next_run_exact_next_minute() {
-    let (now_min, now_hour, now_wd) = now_components();
+fn compute_next_run_is_within_next_minute() {
+    let schedule = CronParser::new().parse("* * * * *").unwrap();
+
+    let start = SystemTime::now();
+    let when = compute_next_run(&schedule, &TimeZone::utc(), DstPolicy::Shift).unwrap();
+    let dur = when.duration_since(start).unwrap();
+
+    assert!(
+        dur <= Duration::from_secs(60),
+        "expected at most 60s ahead, got {:?}",
+        dur
+    );
+    assert_eq!(system_time_to_unix(when) % 60, 0);
+}
+
+#[test]
+fn next_run_is_strictly_after_reference() {
+    // Exactly on a matching minute: the next run is the following one.
+    assert_eq!(
+        next("*/15 * * * *", at(2025, 3, 10, 12, 15)),
+        Some(dt(2025, 3, 10, 12, 30))
+    );
+
+    // Mid-minute reference rounds up to the next whole minute.
+    let mid = at(2025, 3, 10, 12, 15) + Duration::from_secs(30);
+    assert_eq!(next("* * * * *", mid), Some(dt(2025, 3, 10, 12, 16)));
+}
+
+#[test]
+fn honours_day_of_month() {
+    // Monthly at 02:00 on the 1st, not every night.
+    assert_eq!(
+        next("0 2 1 * *", at(2025, 3, 10, 12, 0)),
+        Some(dt(2025, 4, 1, 2, 0))
+    );
+    assert_eq!(
+        next("0 2 1 * *", at(2025, 12, 1, 2, 0)),
+        Some(dt(2026, 1, 1, 2, 0))
+    );
+}
 
-    let next_min = (now_min + 1) % 60;
-    let carry_hour = if next_min == 0 { 1 } else { 0 };
-    let next_hour = (now_hour + carry_hour) % 24;
-    let carry_day = if carry_hour == 1 && next_hour == 0 {
-        1
-    } else {
-        0
+#[test]
+fn honours_month() {
+    assert_eq!(
+        next("0 0 * 12 *", at(2025, 3, 10, 12, 0)),
+        Some(dt(2025, 12, 1, 0, 0))
+    );
+    assert_eq!(
+        next("0 0 * 12 *", at(2025, 12, 31, 0, 0)),
+        Some(dt(2026, 12, 1, 0, 0))
+    );
+}
+
+#[test]
+fn skips_months_without_the_day() {
+    // The 31st only exists in seven months.
+    assert_eq!(
+        next("0 0 31 * *", at(2025, 3, 31, 0, 0)),
+        Some(dt(2025, 5, 31, 0, 0))
+    );
+}
+
+#[test]
+fn handles_leap_years() {
+    assert_eq!(
+        next("0 12 29 2 *", at(2025, 1, 1, 0, 0)),
+        Some(dt(2028, 2, 29, 12, 0))
+    );
+    // 2100 is not a leap year.
+    assert_eq!(
+        next("0 12 29 2 *", at(2096, 3, 1, 0, 0)),
+        Some(dt(2104, 2, 29, 12, 0))
+    );
+}
+
+#[test]
+fn restricted_day_and_weekday_match_either() {
+    // The 1st, the 15th and every Monday.
+    let after = at(2025, 3, 1, 0, 0); // a Saturday
+    assert_eq!(next("0 0 1,15 * 1", after), Some(dt(2025, 3, 3, 0, 0)));
+    assert_eq!(
+        next("0 0 1,15 * 1", at(2025, 3, 10, 0, 0)),
+        Some(dt(2025, 3, 15, 0, 0))
+    );
+
+    // An unrestricted weekday does not widen the day-of-month.
+    assert_eq!(next("0 0 15 * *", after), Some(dt(2025, 3, 15, 0, 0)));
+    // And vice versa.
+    assert_eq!(next("0 0 * * 5", after), Some(dt(2025, 3, 7, 0, 0)));
+}
+
+#[test]
+fn strict_day_match_requires_both() {
+    // Friday the 13th.
+    let mut schedule = CronParser::new().parse("0 0 13 * 5").unwrap();
+    schedule.day_match = DayMatch::And;
+    let when = next_run_after(
+        &schedule,
+        &TimeZone::utc(),
+        DstPolicy::Shift,
+        at(2025, 1, 1, 0, 0),
+    )
+    .unwrap();
+    assert_eq!(
+        DateTime::from_unix(system_time_to_unix(when)),
+        dt(2025, 6, 13, 0, 0)
+    );
+}
+
+#[test]
+fn rolls_over_hours_days_and_years() {
+    assert_eq!(
+        next("5 * * * *", at(2025, 12, 31, 23, 30)),
+        Some(dt(2026, 1, 1, 0, 5))
+    );
+    assert_eq!(
+        next("30 9 * * 1", at(2025, 3, 10, 9, 30)),
+        Some(dt(2025, 3, 17, 9, 30))
+    );
+}
+
+#[test]
+fn impossible_schedule_returns_none() {
+    assert_eq!(next("0 0 30 2 *", at(2025, 1, 1, 0, 0)), None);
+}
+
+#[test]
+fn evaluates_fields_in_job_timezone() {
+    let schedule = CronParser::new().parse("0 2 * * *").unwrap();
+    let zagreb = TimeZone::from_posix("CET-1CEST,M3.5.0,M10.5.0/3").unwrap();
+
+    // Winter: 02:00 CET is 01:00 UTC.
+    let when =
+        next_run_after(&schedule, &zagreb, DstPolicy::Shift, at(2025, 1, 10, 12, 0)).unwrap();
+    assert_eq!(
+        DateTime::from_unix(system_time_to_unix(when)),
+        dt(2025, 1, 11, 1, 0)
+    );
+
+    // Summer: 02:00 CEST is 00:00 UTC.
+    let when =
+        next_run_after(&schedule, &zagreb, DstPolicy::Shift, at(2025, 7, 10, 12, 0)).unwrap();
+    assert_eq!(
+        DateTime::from_unix(system_time_to_unix(when)),
+        dt(2025, 7, 11, 0, 0)
+    );
+}
+
+#[test]
+fn six_field_schedules_fire_at_second_granularity() {
+    let schedule = CronParser::new().parse("*/10 * * * * *").unwrap();
+    let after = at(2025, 3, 10, 12, 0) + Duration::from_secs(5);
+    let when = next_run_after(&schedule, &TimeZone::utc(), DstPolicy::Shift, after).unwrap();
+    assert_eq!(
+        DateTime::from_unix(system_time_to_unix(when)),
+        DateTime::new(2025, 3, 10, 12, 0, 10)
+    );
+
+    // Seconds roll over into the next minute and hour.
+    let schedule = CronParser::new().parse("15 59 * * * *").unwrap();
+    let after = at(2025, 3, 10, 12, 59) + Duration::from_secs(15);
+    let when = next_run_after(&schedule, &TimeZone::utc(), DstPolicy::Shift, after).unwrap();
+    assert_eq!(
+        DateTime::from_unix(system_time_to_unix(when)),
+        DateTime::new(2025, 3, 10, 13, 59, 15)
+    );
+}
+
+#[test]
+fn calendar_relative_day_rules() {
+    let after = at(2025, 1, 1, 0, 0);
+
+    // Last day of February, and two days before it.
+    assert_eq!(next("0 0 L 2 *", after), Some(dt(2025, 2, 28, 0, 0)));
+    assert_eq!(next("0 0 L-2 2 *", after), Some(dt(2025, 2, 26, 0, 0)));
+
+    // 2025-05-31 is a Saturday, so the last weekday is Friday the 30th.
+    assert_eq!(next("0 0 LW 5 *", after), Some(dt(2025, 5, 30, 0, 0)));
+
+    // 2025-06-15 is a Sunday: Monday the 16th. 2025-03-01 is a Saturday
+    // and the Friday before is in February: Monday the 3rd.
+    assert_eq!(next("0 0 15W 6 *", after), Some(dt(2025, 6, 16, 0, 0)));
+    assert_eq!(next("0 0 1W 3 *", after), Some(dt(2025, 3, 3, 0, 0)));
+
+    // Last Friday and second Tuesday of January 2025.
+    assert_eq!(next("0 0 ? 1 5L", after), Some(dt(2025, 1, 31, 0, 0)));
+    assert_eq!(next("0 0 ? 1 2#2", after), Some(dt(2025, 1, 14, 0, 0)));
+
+    // A fifth Monday only exists in some months.
+    assert_eq!(next("0 0 * * MON#5", after), Some(dt(2025, 3, 31, 0, 0)));
+}
+
+#[test]
+fn interval_ticks_follow_the_anchor_grid() {
+    use croner::parser::IntervalSchedule;
+
+    let start = DateTime::new(2025, 3, 10, 12, 0, 0).to_unix();
+    let every_7m = IntervalSchedule {
+        every: 420,
+        anchor: None,
+        exclude: Vec::new(),
     };
-    let next_wd = (now_wd + carry_day) % 7;
+    // Without an anchor, ticks count from the start time.
+    assert_eq!(every_7m.next_after(start, start), Some(start + 420));
+    assert_eq!(every_7m.next_after(start + 419, start), Some(start + 420));
+    assert_eq!(every_7m.next_after(start + 420, start), Some(start + 840));
 
-    let schedule = make_schedule(bit_u64(next_min), bit_u32(next_hour), bit_u8(next_wd));
+    // An anchor fixes the phase, before or after it.
+    let anchored = IntervalSchedule {
+        every: 420,
+        anchor: Some(start + 60),
+        exclude: Vec::new(),
+    };
+    assert_eq!(anchored.next_after(start, start), Some(start + 60));
+    assert_eq!(anchored.next_after(start + 60, start), Some(start + 480));
+    assert_eq!(anchored.next_after(start - 400, start), Some(start - 360));
 
-    let start = Instant::now();
-    let when = compute_next_run(&schedule);
-    let dur = when.duration_since(start);
+    // A tick past the end of time is no tick at all
+    let endless = IntervalSchedule {
+        every: i64::MAX as u64,
+        anchor: None,
+        exclude: Vec::new(),
+    };
+    assert_eq!(endless.next_after(start, start), None);
+}
 
-    assert!(
-        dur >= Duration::from_secs(60) && dur <= Duration::from_secs(62),
-        "expected ~60s ahead, got {:?}",
-        dur
+#[test]
+fn year_field_bounds_the_schedule() {
+    // Skips ahead to the first allowed year.
+    assert_eq!(
+        next("0 0 9 1 * * 2027", at(2025, 3, 10, 0, 0)),
+        Some(dt(2027, 1, 1, 9, 0))
     );
+    // Nothing once the last year is over.
+    assert_eq!(next("0 0 9 1 * * 2025", at(2025, 12, 1, 9, 0)), None);
 }
 
 #[test]
-fn compute_next_run_respects_longer_gap() {
-    let (now_min, now_hour, now_wd) = now_components();
+fn excluded_days_are_skipped() {
+    use croner::calendar::Calendar;
+    use croner::parser::IntervalSchedule;
+    use std::sync::Arc;
 
-    let target_min = (now_min + 2) % 60;
-    let carry_hour = if target_min < now_min { 1 } else { 0 };
-    let target_hour = (now_hour + carry_hour) % 24;
-    let carry_day = if carry_hour == 1 && target_hour == 0 {
-        1
-    } else {
-        0
+    let mut holidays = Calendar::new("holidays");
+    holidays.add("2025-12-24..2025-12-26").unwrap();
+    holidays.add("2026-01-01").unwrap();
+    let holidays = Arc::new(holidays);
+
+    // Daily at 09:00, but not over Christmas or on New Year's Day.
+    let mut schedule = CronParser::new().parse("0 9 * * *").unwrap();
+    schedule.exclude = vec![holidays.clone()];
+    let after = at(2025, 12, 23, 12, 0);
+    let when = next_run_after(&schedule, &TimeZone::utc(), DstPolicy::Shift, after).unwrap();
+    assert_eq!(
+        DateTime::from_unix(system_time_to_unix(when)),
+        dt(2025, 12, 27, 9, 0)
+    );
+    let after = at(2025, 12, 31, 12, 0);
+    let when = next_run_after(&schedule, &TimeZone::utc(), DstPolicy::Shift, after).unwrap();
+    assert_eq!(
+        DateTime::from_unix(system_time_to_unix(when)),
+        dt(2026, 1, 2, 9, 0)
+    );
+
+    // Intervals resume on the first tick of the next open day.
+    let start = DateTime::new(2025, 12, 23, 0, 0, 0).to_unix();
+    let hourly = IntervalSchedule {
+        every: 3600,
+        anchor: Some(start + 1800),
+        exclude: vec![holidays],
     };
-    let target_wd = (now_wd + carry_day) % 7;
+    let last = DateTime::new(2025, 12, 23, 23, 30, 0).to_unix();
+    assert_eq!(
+        hourly.next_after_in(last, start, &TimeZone::utc()),
+        Some(DateTime::new(2025, 12, 27, 0, 30, 0).to_unix())
+    );
+}
 
-    let schedule = make_schedule(bit_u64(target_min), bit_u32(target_hour), bit_u8(target_wd));
+#[test]
+fn upcoming_and_previous_runs() {
+    let schedule = CronParser::new().parse("30 9 * * MON-FRI").unwrap();
+    let friday = at(2025, 3, 14, 12, 0);
 
-    let start = Instant::now();
-    let when = compute_next_run(&schedule);
-    let dur = when.duration_since(start);
+    assert_eq!(schedule.next_after(friday), Some(at(2025, 3, 17, 9, 30)));
+    assert_eq!(schedule.prev_before(friday), Some(at(2025, 3, 14, 9, 30)));
+    // Strictly before: a run exactly at `t` is not its own predecessor.
+    assert_eq!(
+        schedule.prev_before(at(2025, 3, 14, 9, 30)),
+        Some(at(2025, 3, 13, 9, 30))
+    );
+    assert_eq!(
+        schedule.prev_before(at(2025, 3, 14, 9, 30) + Duration::from_millis(1)),
+        Some(at(2025, 3, 14, 9, 30))
+    );
 
-    assert!(
-        dur >= Duration::from_secs(120) && dur <= Duration::from_secs(122),
-        "expected ~120s ahead, got {:?}",
-        dur
+    let next_five: Vec<SystemTime> = schedule.iter_after(friday).take(5).collect();
+    assert_eq!(
+        next_five,
+        vec![
+            at(2025, 3, 17, 9, 30),
+            at(2025, 3, 18, 9, 30),
+            at(2025, 3, 19, 9, 30),
+            at(2025, 3, 20, 9, 30),
+            at(2025, 3, 21, 9, 30),
+        ]
+    );
+
+    // Sparse schedules are found far back, impossible ones end.
+    let leap = CronParser::new().parse("0 12 29 2 *").unwrap();
+    assert_eq!(
+        leap.prev_before(at(2027, 1, 1, 0, 0)),
+        Some(at(2024, 2, 29, 12, 0))
+    );
+    let never = CronParser::new().parse("0 0 30 2 *").unwrap();
+    assert_eq!(never.prev_before(friday), None);
+    assert_eq!(never.iter_after(friday).next(), None);
+
+    // Dense schedules far back cost no more than sparse ones
+    let every_second_1990 = CronParser::new().parse("* * * * * * 1990").unwrap();
+    assert_eq!(
+        every_second_1990.prev_before(friday),
+        Some(at(1991, 1, 1, 0, 0) - Duration::from_secs(1))
+    );
+    let every_minute_2020 = CronParser::new().parse("0 * * * * * 2020").unwrap();
+    assert_eq!(
+        every_minute_2020.prev_before(friday),
+        Some(at(2020, 12, 31, 23, 59))
+    );
+}
+
+#[test]
+fn overlap_policy_decides_due_runs() {
+    use OverlapAction::*;
+
+    // Nothing in flight: every policy starts the run
+    for policy in [
+        Overlap::Allow,
+        Overlap::Skip,
+        Overlap::Queue,
+        Overlap::Replace,
+    ] {
+        assert_eq!(overlap_action(policy, false, false), Start);
+    }
+
+    assert_eq!(overlap_action(Overlap::Allow, true, false), Start);
+    assert_eq!(overlap_action(Overlap::Skip, true, false), Skip);
+    assert_eq!(overlap_action(Overlap::Replace, true, false), Replace);
+
+    // Queue holds one run while the earlier one is active, drops any more,
+    // and lets it start once the earlier run has ended
+    assert_eq!(overlap_action(Overlap::Queue, true, false), Queue);
+    assert_eq!(overlap_action(Overlap::Queue, true, true), SkipQueued);
+    assert_eq!(overlap_action(Overlap::Queue, false, true), Start);
+}
+
+/// Instances as (job id, max_parallel, label), oldest first.
+type Waiting = VecDeque<(&'static str, Option<usize>, u32)>;
+
+/// Runs `start_in_order`, returning the labels started and those left.
+fn start(
+    waiting: &[(&'static str, Option<usize>, u32)],
+    running: &mut HashMap<String, usize>,
+    max_concurrent: Option<usize>,
+) -> (Vec<u32>, Vec<u32>) {
+    let mut started = Vec::new();
+    let left = start_in_order(
+        waiting.iter().copied().collect::<Waiting>(),
+        running,
+        max_concurrent,
+        |&(id, max_parallel, _)| (id, max_parallel),
+        |(_, _, label)| {
+            started.push(label);
+            true
+        },
+    );
+    (
+        started,
+        left.into_iter().map(|(_, _, label)| label).collect(),
+    )
+}
+
+#[test]
+fn limits_cap_running_instances() {
+    let waiting = [
+        ("a", None, 1),
+        ("a", None, 2),
+        ("b", None, 3),
+        ("b", None, 4),
+    ];
+
+    let mut running = HashMap::new();
+    assert_eq!(
+        start(&waiting, &mut running, None),
+        (vec![1, 2, 3, 4], vec![])
+    );
+
+    let mut running = HashMap::new();
+    assert_eq!(
+        start(&waiting, &mut running, Some(3)),
+        (vec![1, 2, 3], vec![4])
+    );
+    assert_eq!(running.values().sum::<usize>(), 3);
+
+    // Runs already in flight count against both limits
+    let mut running = HashMap::from([("c".to_string(), 2)]);
+    assert_eq!(
+        start(&waiting, &mut running, Some(3)),
+        (vec![1], vec![2, 3, 4])
+    );
+
+    let waiting = [("a", Some(2), 1), ("a", Some(2), 2), ("a", Some(2), 3)];
+    let mut running = HashMap::from([("a".to_string(), 1)]);
+    assert_eq!(start(&waiting, &mut running, None), (vec![1], vec![2, 3]));
+    assert_eq!(running["a"], 2);
+}
+
+#[test]
+fn waiting_instances_start_oldest_first() {
+    let waiting = [
+        ("a", None, 1),
+        ("b", None, 2),
+        ("a", None, 3),
+        ("c", None, 4),
+    ];
+    let mut running = HashMap::new();
+    assert_eq!(
+        start(&waiting, &mut running, Some(2)),
+        (vec![1, 2], vec![3, 4])
+    );
+
+    // One run ends: the oldest waiting instance takes its slot
+    *running.get_mut("a").unwrap() -= 1;
+    assert_eq!(
+        start(&waiting[2..], &mut running, Some(2)),
+        (vec![3], vec![4])
+    );
+}
+
+#[test]
+fn a_job_at_its_own_limit_does_not_block_others() {
+    let waiting = [
+        ("a", Some(1), 1),
+        ("a", Some(1), 2),
+        ("b", None, 3),
+        ("a", Some(1), 4),
+    ];
+    let mut running = HashMap::new();
+    assert_eq!(
+        start(&waiting, &mut running, None),
+        (vec![1, 3], vec![2, 4])
+    );
+}
+
+#[test]
+fn instances_that_fail_to_start_free_their_slot() {
+    let waiting: Waiting = [("a", Some(1), 1), ("a", Some(1), 2)].into();
+    let mut running = HashMap::new();
+    let mut tried = Vec::new();
+    let left = start_in_order(
+        waiting,
+        &mut running,
+        Some(1),
+        |&(id, max_parallel, _)| (id, max_parallel),
+        |(_, _, label)| {
+            tried.push(label);
+            label != 1
+        },
     );
+    assert_eq!(tried, vec![1, 2]);
+    assert!(left.is_empty());
+    assert_eq!(running["a"], 1);
 }
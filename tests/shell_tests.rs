@@ -1,6 +1,6 @@
 // tests/shell_tests.rs
 
-use croner::shell::{get_command, get_command_as_os_str};
+use croner::shell::{get_command, get_command_as_os_str, Shell};
 use std::ffi::OsString;
 
 #[test]
@@ -122,3 +122,29 @@ fn invalid_utf8_token_returns_error_windows() {
 
     assert_eq!(parsed.first().map(String::as_str), Some("echo"));
 }
+
+#[test]
+fn shell_names_are_recognised() {
+    assert_eq!(Shell::from_name("none"), Some(Shell::None));
+    assert_eq!(Shell::from_name("bash"), Some(Shell::Bash));
+    assert_eq!(Shell::from_name("powershell"), Some(Shell::PowerShell));
+    assert_eq!(Shell::from_name("zsh"), None);
+    assert_eq!(Shell::default(), Shell::None);
+}
+
+#[test]
+fn commands_are_built_from_argv() {
+    let argv = get_command_as_os_str("printf '%s\\n' \"a b\"");
+    let cmd = Shell::None.command(&argv).expect("command");
+    assert_eq!(cmd.get_program(), "printf");
+    let args: Vec<_> = cmd.get_args().collect();
+    assert_eq!(args, ["%s\\n", "a b"]);
+
+    let line = [OsString::from("echo $HOME")];
+    let cmd = Shell::Sh.command(&line).expect("command");
+    assert_eq!(cmd.get_program(), "sh");
+    let args: Vec<_> = cmd.get_args().collect();
+    assert_eq!(args, ["-c", "echo $HOME"]);
+
+    assert!(Shell::None.command(&[]).is_none());
+}
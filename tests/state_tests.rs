@@ -0,0 +1,72 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use croner::state::FiredLog;
+
+fn temp_path(name: &str) -> PathBuf {
+    let mut p = std::env::temp_dir();
+    let nanos = SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_nanos();
+    p.push(format!("{}_{}", name, nanos));
+    p
+}
+
+#[test]
+fn state_file_sits_next_to_the_config() {
+    assert_eq!(
+        FiredLog::path_for(Path::new("/etc/croner/jobs.croner")),
+        PathBuf::from("/etc/croner/jobs.croner.state")
+    );
+}
+
+#[test]
+fn recorded_runs_survive_reopening() {
+    let p = temp_path("fired.state");
+
+    let mut log = FiredLog::open(&p).expect("missing file is empty");
+    assert!(!log.contains("migrate db", 1_700_000_000));
+    log.record("migrate db", 1_700_000_000).unwrap();
+    log.record("migrate db", 1_700_000_000).unwrap();
+    assert!(log.contains("migrate db", 1_700_000_000));
+
+    let log = FiredLog::open(&p).unwrap();
+    assert!(log.contains("migrate db", 1_700_000_000));
+    // A new instant for the same job is a new one-shot.
+    assert!(!log.contains("migrate db", 1_800_000_000));
+    assert_eq!(fs::read_to_string(&p).unwrap().lines().count(), 1);
+
+    let _ = fs::remove_file(&p);
+}
+
+#[test]
+fn malformed_lines_are_skipped() {
+    let p = temp_path("torn.state");
+
+    // A write torn by a crash leaves a partial last line
+    fs::write(
+        &p,
+        "1700000000 backup\ngarbage\n\n1700000600 migrate db\n17000",
+    )
+    .unwrap();
+    let mut log = FiredLog::open(&p).expect("valid entries are kept");
+    assert!(log.contains("backup", 1_700_000_000));
+    assert!(log.contains("migrate db", 1_700_000_600));
+    assert_eq!(log.skipped_lines(), &[2, 5]);
+
+    // New entries start on a line of their own
+    log.record("report", 1_700_001_200).unwrap();
+    let log = FiredLog::open(&p).unwrap();
+    assert!(log.contains("report", 1_700_001_200));
+    assert_eq!(log.skipped_lines(), &[2, 5]);
+    let _ = fs::remove_file(&p);
+}
+
+#[test]
+fn in_memory_log_writes_nothing() {
+    let mut log = FiredLog::in_memory();
+    log.record("job", 42).unwrap();
+    assert!(log.contains("job", 42));
+}
@@ -0,0 +1,233 @@
+use std::time::Duration;
+
+use croner::supervisor::RunStatus;
+
+#[test]
+fn describes_how_a_run_ended() {
+    let status = RunStatus {
+        code: Some(2),
+        signal: None,
+        elapsed: Duration::from_millis(4_320),
+        max_rss: None,
+        timed_out: false,
+        stopped: false,
+    };
+    assert_eq!(status.to_string(), "exited 2 after 4.3s");
+    assert!(!status.success());
+
+    let status = RunStatus {
+        code: None,
+        signal: Some(9),
+        elapsed: Duration::from_secs(3_725),
+        max_rss: Some(18 * 1024 * 1024 + 300 * 1024),
+        timed_out: false,
+        stopped: false,
+    };
+    assert_eq!(
+        status.to_string(),
+        "killed by SIGKILL after 1h2m5s (peak RSS 18.3 MiB)"
+    );
+
+    let status = RunStatus {
+        code: None,
+        signal: Some(31),
+        elapsed: Duration::ZERO,
+        max_rss: Some(512),
+        timed_out: false,
+        stopped: false,
+    };
+    assert_eq!(
+        status.to_string(),
+        "killed by signal 31 after 0.0s (peak RSS 512 B)"
+    );
+
+    let status = RunStatus {
+        code: None,
+        signal: Some(15),
+        elapsed: Duration::from_secs(600),
+        max_rss: None,
+        timed_out: true,
+        stopped: false,
+    };
+    assert_eq!(status.to_string(), "timed out, killed by SIGTERM after 10m");
+}
+
+#[cfg(unix)]
+fn sh(script: &str) -> std::process::Command {
+    let mut cmd = std::process::Command::new("sh");
+    cmd.args(["-c", script]);
+    cmd
+}
+
+#[cfg(unix)]
+#[test]
+fn waits_for_exit_codes_and_signals() {
+    use croner::supervisor::spawn;
+
+    let status = spawn(&mut sh("sleep 0.3; exit 3"))
+        .unwrap()
+        .wait(None, Duration::ZERO);
+    let status = status.expect("wait");
+    assert_eq!((status.code, status.signal), (Some(3), None));
+    assert!(status.max_rss.is_some());
+    assert!(!status.timed_out);
+
+    let process = spawn(&mut sh("true")).unwrap();
+    assert!(process.wait(None, Duration::ZERO).expect("wait").success());
+
+    let process = spawn(&mut sh("sleep 0.2; kill -TERM $$")).unwrap();
+    let status = process.wait(None, Duration::ZERO).expect("wait");
+    assert_eq!((status.code, status.signal), (None, Some(15)));
+    assert!(status.elapsed >= Duration::from_millis(200));
+}
+
+#[cfg(unix)]
+#[test]
+fn stops_runs_that_exceed_their_timeout() {
+    use croner::supervisor::spawn;
+
+    let timeout = Some(Duration::from_millis(200));
+    let grace = Duration::from_millis(300);
+
+    // Finishing in time is not a timeout
+    let status = spawn(&mut sh("exit 0")).unwrap().wait(timeout, grace);
+    assert!(!status.expect("wait").timed_out);
+
+    // SIGTERM is enough
+    let status = spawn(&mut sh("sleep 5")).unwrap().wait(timeout, grace);
+    let status = status.expect("wait");
+    assert!(status.timed_out);
+    assert_eq!(status.signal, Some(15));
+    assert!(status.elapsed < Duration::from_secs(2));
+
+    // Ignoring SIGTERM earns a SIGKILL once the grace period is over
+    let status = spawn(&mut sh("trap '' TERM; sleep 5"))
+        .unwrap()
+        .wait(timeout, grace);
+    let status = status.expect("wait");
+    assert!(status.timed_out);
+    assert_eq!(status.signal, Some(9));
+    assert!(status.elapsed >= Duration::from_millis(500));
+    assert!(status.elapsed < Duration::from_secs(2));
+}
+
+#[cfg(unix)]
+#[test]
+fn survives_an_unbounded_grace_period() {
+    use croner::supervisor::spawn;
+
+    let status = spawn(&mut sh("sleep 5"))
+        .unwrap()
+        .wait(Some(Duration::from_millis(100)), Duration::MAX);
+    let status = status.expect("wait");
+    assert!(status.timed_out);
+    assert_eq!(status.signal, Some(15));
+}
+
+#[cfg(unix)]
+#[test]
+fn stoppers_end_a_run_early() {
+    use croner::supervisor::spawn;
+
+    let process = spawn(&mut sh("sleep 5")).unwrap();
+    let stopper = process.stopper();
+    let waiter = std::thread::spawn(move || process.wait(None, Duration::from_secs(1)));
+    std::thread::sleep(Duration::from_millis(100));
+    stopper.stop();
+
+    let status = waiter.join().unwrap().expect("wait");
+    assert!(status.stopped && !status.timed_out);
+    assert_eq!(status.signal, Some(15));
+    assert!(status
+        .to_string()
+        .starts_with("stopped, killed by SIGTERM after"));
+
+    // Too late to matter once the run is over
+    stopper.stop();
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn kills_what_the_leader_leaves_behind() {
+    use croner::supervisor::spawn;
+
+    let pid_file = std::env::temp_dir().join(format!("croner_orphan_{}", std::process::id()));
+    let script = format!(
+        "(trap '' TERM; sleep 5) & echo $! > {}; trap 'exit 0' TERM; wait",
+        pid_file.display()
+    );
+    let status = spawn(&mut sh(&script))
+        .unwrap()
+        .wait(Some(Duration::from_millis(200)), Duration::from_secs(3));
+    let status = status.expect("wait");
+    assert!(status.timed_out);
+    assert_eq!(status.code, Some(0));
+    assert!(status.elapsed < Duration::from_secs(2));
+
+    // The leader exited on SIGTERM; the sleep it left is killed, not orphaned
+    let pid = std::fs::read_to_string(&pid_file).unwrap();
+    let _ = std::fs::remove_file(&pid_file);
+    std::thread::sleep(Duration::from_millis(100));
+    let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid.trim())).unwrap_or_default();
+    let state = stat.rsplit(')').next().unwrap_or("").trim_start();
+    assert!(
+        stat.is_empty() || state.starts_with('Z'),
+        "still running: {}",
+        stat
+    );
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn leaves_the_background_workers_of_a_finished_run_alone() {
+    use std::io::Read;
+    use std::process::Stdio;
+    use std::sync::mpsc;
+
+    use croner::supervisor::spawn;
+
+    // The worker keeps the run's stdout open after the leader has exited
+    let pid_file = std::env::temp_dir().join(format!("croner_worker_{}", std::process::id()));
+    let script = format!("sleep 5 & echo $! > {}", pid_file.display());
+    let mut cmd = sh(&script);
+    cmd.stdout(Stdio::piped());
+    let mut process = spawn(&mut cmd).unwrap();
+    let mut stdout = process.child.stdout.take().unwrap();
+    let status = process.wait(None, Duration::from_secs(3)).expect("wait");
+    assert_eq!(status.code, Some(0));
+    assert!(!status.timed_out && !status.stopped);
+    assert!(status.elapsed < Duration::from_secs(1));
+
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        let mut out = String::new();
+        let _ = stdout.read_to_string(&mut out);
+        let _ = tx.send(());
+    });
+    let still_open = rx.recv_timeout(Duration::from_millis(200)).is_err();
+
+    let pid = std::fs::read_to_string(&pid_file).unwrap();
+    let _ = std::fs::remove_file(&pid_file);
+    let alive = std::path::Path::new(&format!("/proc/{}", pid.trim())).exists();
+    let _ = std::process::Command::new("kill").arg(pid.trim()).status();
+    assert!(alive, "the worker should outlive the run");
+    assert!(still_open, "the worker should still hold the pipe");
+    rx.recv_timeout(Duration::from_secs(2))
+        .expect("pipe closed");
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn reports_the_jobs_own_memory() {
+    use croner::supervisor::spawn;
+
+    // Make croner's own peak far larger than anything `sleep` needs
+    let ballast = vec![1u8; 96 * 1024 * 1024];
+    let status = spawn(&mut sh("sleep 0.3"))
+        .unwrap()
+        .wait(None, Duration::ZERO);
+    drop(std::hint::black_box(ballast));
+
+    let rss = status.expect("wait").max_rss.expect("sampled");
+    assert!(rss > 0 && rss < 32 * 1024 * 1024, "peak RSS {}", rss);
+}
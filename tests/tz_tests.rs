@@ -0,0 +1,278 @@
+use croner::datetime::DateTime;
+use croner::parser::{CronParser, DstPolicy};
+use croner::tz::TimeZone;
+
+fn utc(year: i32, month: u8, day: u8, hour: u8, minute: u8) -> i64 {
+    DateTime::new(year, month, day, hour, minute, 0).to_unix()
+}
+
+fn central_europe() -> TimeZone {
+    TimeZone::from_posix("CET-1CEST,M3.5.0,M10.5.0/3").unwrap()
+}
+
+#[test]
+fn utc_has_no_offset_or_transitions() {
+    let tz = TimeZone::utc();
+    assert_eq!(tz.offset_at(utc(2025, 6, 1, 0, 0)), 0);
+    assert_eq!(tz.next_transition(utc(2025, 6, 1, 0, 0)), None);
+}
+
+#[test]
+fn posix_rule_offsets_and_transitions() {
+    let tz = central_europe();
+    assert_eq!(tz.offset_at(utc(2025, 1, 15, 12, 0)), 3600);
+    assert_eq!(tz.offset_at(utc(2025, 7, 15, 12, 0)), 7200);
+
+    // 2025-03-30 01:00 UTC and 2025-10-26 01:00 UTC.
+    assert_eq!(
+        tz.next_transition(utc(2025, 1, 1, 0, 0)),
+        Some(utc(2025, 3, 30, 1, 0))
+    );
+    assert_eq!(
+        tz.next_transition(utc(2025, 3, 30, 1, 0)),
+        Some(utc(2025, 10, 26, 1, 0))
+    );
+    assert_eq!(
+        tz.prev_transition(utc(2025, 7, 1, 0, 0)),
+        Some(utc(2025, 3, 30, 1, 0))
+    );
+}
+
+#[test]
+fn southern_hemisphere_rule() {
+    // Australia/Sydney: DST from first Sunday of October to first Sunday of April.
+    let tz = TimeZone::from_posix("AEST-10AEDT,M10.1.0,M4.1.0/3").unwrap();
+    assert_eq!(tz.offset_at(utc(2025, 1, 15, 0, 0)), 11 * 3600);
+    assert_eq!(tz.offset_at(utc(2025, 7, 15, 0, 0)), 10 * 3600);
+}
+
+#[test]
+fn rejects_out_of_range_offsets() {
+    for spec in ["ABC9999999", "ABC25", "ABC-24:60", "ABC99999999999"] {
+        assert!(TimeZone::from_posix(spec).is_err(), "accepted {}", spec);
+    }
+    assert!(TimeZone::from_posix("ABC24:59:59").is_ok());
+    // Transition times may run past a day
+    assert!(TimeZone::from_posix("EST5EDT,M3.2.0/-2,M11.1.0/26").is_ok());
+    assert!(TimeZone::from_posix("EST5EDT,M3.2.0/168,M11.1.0").is_err());
+    // Rule dates are range-checked before they are narrowed
+    for spec in [
+        "CET-1CEST,M3.5.0,M268.5.0",
+        "CET-1CEST,M3.261.0,M10.5.0",
+        "CET-1CEST,J0,J300",
+        "CET-1CEST,J60,J65596",
+        "CET-1CEST,60,366",
+    ] {
+        assert!(TimeZone::from_posix(spec).is_err(), "accepted {}", spec);
+    }
+    assert!(TimeZone::from_posix("CET-1CEST,J60,J365").is_ok());
+    assert!(TimeZone::from_posix("CET-1CEST,0,365").is_ok());
+}
+
+#[test]
+fn rejects_unknown_zone() {
+    assert!(TimeZone::named("Not/AZone").is_err());
+}
+
+#[test]
+fn loads_system_zoneinfo_when_available() {
+    if !std::path::Path::new("/usr/share/zoneinfo/Europe/Zagreb").exists() {
+        return;
+    }
+    let tz = TimeZone::named("Europe/Zagreb").unwrap();
+    assert_eq!(tz.name(), "Europe/Zagreb");
+
+    // Historical table and the footer rule agree with the POSIX definition.
+    for t in [
+        utc(2010, 1, 1, 0, 0),
+        utc(2010, 7, 1, 0, 0),
+        utc(2045, 1, 1, 0, 0),
+        utc(2045, 7, 1, 0, 0),
+    ] {
+        assert_eq!(tz.offset_at(t), central_europe().offset_at(t));
+    }
+    assert_eq!(
+        tz.next_transition(utc(2045, 1, 1, 0, 0)),
+        central_europe().next_transition(utc(2045, 1, 1, 0, 0))
+    );
+}
+
+/// All runs in `[from, to)` under the given policy.
+fn runs(expr: &str, policy: DstPolicy, from: i64, to: i64) -> Vec<i64> {
+    let s = CronParser::new().parse(expr).unwrap();
+    let tz = central_europe();
+    let mut out = Vec::new();
+    let mut t = from - 1;
+    while let Some(next) = s.next_after_in(t, &tz, policy) {
+        if next >= to {
+            break;
+        }
+        out.push(next);
+        t = next;
+    }
+    out
+}
+
+#[test]
+fn spring_forward_gap_policies() {
+    // 02:30 does not exist on 2025-03-30 in Zagreb (01:00 UTC jumps 02:00 -> 03:00).
+    let from = utc(2025, 3, 29, 12, 0);
+    let to = utc(2025, 3, 31, 12, 0);
+    let shift = utc(2025, 3, 30, 1, 0);
+    let next_day = utc(2025, 3, 31, 0, 30);
+
+    assert_eq!(
+        runs("30 2 * * *", DstPolicy::Skip, from, to),
+        vec![next_day]
+    );
+    assert_eq!(
+        runs("30 2 * * *", DstPolicy::Shift, from, to),
+        vec![shift, next_day]
+    );
+    assert_eq!(
+        runs("30 2 * * *", DstPolicy::Twice, from, to),
+        vec![shift, next_day]
+    );
+
+    // Several runs inside the gap collapse into one at the shift.
+    let to = utc(2025, 3, 30, 12, 0);
+    assert_eq!(
+        runs("*/15 2 * * *", DstPolicy::Shift, from, to),
+        vec![shift]
+    );
+}
+
+#[test]
+fn fall_back_overlap_policies() {
+    // 02:30 happens twice on 2025-10-26 in Zagreb: 00:30 UTC and 01:30 UTC.
+    let from = utc(2025, 10, 25, 12, 0);
+    let to = utc(2025, 10, 27, 12, 0);
+    let first = utc(2025, 10, 26, 0, 30);
+    let second = utc(2025, 10, 26, 1, 30);
+    let next_day = utc(2025, 10, 27, 1, 30);
+
+    assert_eq!(
+        runs("30 2 * * *", DstPolicy::Skip, from, to),
+        vec![first, next_day]
+    );
+    assert_eq!(
+        runs("30 2 * * *", DstPolicy::Shift, from, to),
+        vec![first, next_day]
+    );
+    assert_eq!(
+        runs("30 2 * * *", DstPolicy::Twice, from, to),
+        vec![first, second, next_day]
+    );
+}
+
+#[test]
+fn hourly_schedules_run_through_both_passes() {
+    // Zagreb repeats 02:00-03:00 on 2025-10-26, from 00:00 to 02:00 UTC.
+    let from = utc(2025, 10, 25, 23, 30);
+    let to = utc(2025, 10, 26, 2, 10);
+    let every_20: Vec<i64> = (0..8).map(|i| from + 10 * 60 + i * 20 * 60).collect();
+    let hourly = vec![
+        utc(2025, 10, 26, 0, 0),
+        utc(2025, 10, 26, 1, 0),
+        utc(2025, 10, 26, 2, 0),
+    ];
+
+    for policy in [DstPolicy::Skip, DstPolicy::Shift, DstPolicy::Twice] {
+        assert_eq!(runs("*/20 * * * *", policy, from, to), every_20);
+        assert_eq!(runs("0 * * * *", policy, from, to), hourly);
+    }
+
+    // A fixed hour still runs once
+    assert_eq!(
+        runs("0,30 2 * * *", DstPolicy::Shift, from, to),
+        vec![utc(2025, 10, 26, 0, 0), utc(2025, 10, 26, 0, 30)]
+    );
+}
+
+#[test]
+fn starting_inside_the_repeated_hour() {
+    let s = CronParser::new().parse("45 2 * * *").unwrap();
+    let tz = central_europe();
+    // 01:10 UTC is 02:10 CET, the second pass; 02:45 CEST already ran.
+    let after = utc(2025, 10, 26, 1, 10);
+    assert_eq!(
+        s.next_after_in(after, &tz, DstPolicy::Shift),
+        Some(utc(2025, 10, 27, 1, 45))
+    );
+    assert_eq!(
+        s.next_after_in(after, &tz, DstPolicy::Twice),
+        Some(utc(2025, 10, 26, 1, 45))
+    );
+}
+
+#[test]
+fn dst_policy_names() {
+    assert_eq!(DstPolicy::from_name("skip"), Some(DstPolicy::Skip));
+    assert_eq!(DstPolicy::from_name("shift"), Some(DstPolicy::Shift));
+    assert_eq!(DstPolicy::from_name("twice"), Some(DstPolicy::Twice));
+    assert_eq!(DstPolicy::from_name("never"), None);
+    assert_eq!(DstPolicy::default(), DstPolicy::Shift);
+}
+
+#[test]
+fn wall_clock_to_utc() {
+    let tz = central_europe();
+
+    assert_eq!(
+        tz.to_utc(&DateTime::new(2025, 1, 10, 12, 0, 0)),
+        utc(2025, 1, 10, 11, 0)
+    );
+    // Ambiguous: the first (CEST) reading wins.
+    assert_eq!(
+        tz.to_utc(&DateTime::new(2025, 10, 26, 2, 30, 0)),
+        utc(2025, 10, 26, 0, 30)
+    );
+    // Nonexistent: pushed forward by the gap, to 03:30 CEST.
+    assert_eq!(
+        tz.to_utc(&DateTime::new(2025, 3, 30, 2, 30, 0)),
+        utc(2025, 3, 30, 1, 30)
+    );
+}
+
+#[test]
+fn previous_run_mirrors_next_run_across_dst() {
+    let s = CronParser::new().parse("30 2 * * *").unwrap();
+    let tz = central_europe();
+
+    // 02:30 does not exist on 2025-03-30; shift fires at the transition.
+    let after_gap = utc(2025, 3, 30, 12, 0);
+    assert_eq!(
+        s.prev_before_in(after_gap, &tz, DstPolicy::Shift),
+        Some(utc(2025, 3, 30, 1, 0))
+    );
+    assert_eq!(
+        s.prev_before_in(after_gap, &tz, DstPolicy::Skip),
+        Some(utc(2025, 3, 29, 1, 30))
+    );
+
+    // Every run found backwards is the one found forwards.
+    let mut t = utc(2025, 10, 27, 0, 0);
+    for _ in 0..5 {
+        let prev = s.prev_before_in(t, &tz, DstPolicy::Twice).unwrap();
+        assert_eq!(s.next_after_in(prev - 1, &tz, DstPolicy::Twice), Some(prev));
+        t = prev;
+    }
+
+    let upcoming: Vec<i64> = s
+        .iter_after_in(
+            croner::datetime::unix_to_system_time(utc(2025, 10, 25, 12, 0)),
+            tz,
+            DstPolicy::Twice,
+        )
+        .take(3)
+        .map(croner::datetime::system_time_to_unix)
+        .collect();
+    assert_eq!(
+        upcoming,
+        vec![
+            utc(2025, 10, 26, 0, 30),
+            utc(2025, 10, 26, 1, 30),
+            utc(2025, 10, 27, 1, 30)
+        ]
+    );
+}